@@ -3,10 +3,21 @@
 // SPDX-License-Identifier: MIT
 
 use anchor_lang::prelude::*;
-use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::solana_program::{keccak, secp256k1_recover::secp256k1_recover};
 
 declare_id!("11111111111111111111111111111111");
 
+/// Ed25519, the only signature type supported at launch.
+pub const KEY_TYPE_ED25519: u8 = 0;
+/// secp256k1, for Bitcoin/Ethereum-style wallet compatibility.
+pub const KEY_TYPE_SECP256K1: u8 = 1;
+
 pub const MAX_KEYS_PER_AGENT: usize = 5;
 pub const MAX_DID_LEN: usize = 128;
 pub const MAX_NAME_LEN: usize = 64;
@@ -24,6 +35,12 @@ pub mod sage_registry {
         registry.authority = ctx.accounts.authority.key();
         registry.agent_count = 0;
         registry.verification_hook = None;
+        registry.max_did_len = MAX_DID_LEN as u16;
+        registry.max_name_len = MAX_NAME_LEN as u16;
+        registry.max_description_len = MAX_DESCRIPTION_LEN as u16;
+        registry.max_endpoint_len = MAX_ENDPOINT_LEN as u16;
+        registry.max_capabilities_len = MAX_CAPABILITIES_LEN as u16;
+        registry.max_keys_per_agent = MAX_KEYS_PER_AGENT as u8;
         Ok(())
     }
 
@@ -38,17 +55,22 @@ pub mod sage_registry {
         public_keys: Vec<[u8; 32]>,
         key_types: Vec<u8>,
         signatures: Vec<[u8; 64]>,
+        recovery_ids: Vec<u8>,
     ) -> Result<()> {
-        // Validate inputs
-        require!(did.len() <= MAX_DID_LEN, ErrorCode::DIDTooLong);
-        require!(name.len() <= MAX_NAME_LEN, ErrorCode::NameTooLong);
-        require!(description.len() <= MAX_DESCRIPTION_LEN, ErrorCode::DescriptionTooLong);
-        require!(endpoint.len() <= MAX_ENDPOINT_LEN, ErrorCode::EndpointTooLong);
-        require!(capabilities.len() <= MAX_CAPABILITIES_LEN, ErrorCode::CapabilitiesTooLong);
+        // Validate inputs against the registry's configured limits, not the
+        // compile-time defaults, so an authority can raise these caps without
+        // a program upgrade.
+        let limits = &ctx.accounts.registry;
+        require!(did.len() <= limits.max_did_len as usize, ErrorCode::DIDTooLong);
+        require!(name.len() <= limits.max_name_len as usize, ErrorCode::NameTooLong);
+        require!(description.len() <= limits.max_description_len as usize, ErrorCode::DescriptionTooLong);
+        require!(endpoint.len() <= limits.max_endpoint_len as usize, ErrorCode::EndpointTooLong);
+        require!(capabilities.len() <= limits.max_capabilities_len as usize, ErrorCode::CapabilitiesTooLong);
         require!(!public_keys.is_empty(), ErrorCode::NoKeysProvided);
-        require!(public_keys.len() <= MAX_KEYS_PER_AGENT, ErrorCode::TooManyKeys);
+        require!(public_keys.len() <= limits.max_keys_per_agent as usize, ErrorCode::TooManyKeys);
         require!(public_keys.len() == key_types.len(), ErrorCode::KeyArrayMismatch);
         require!(public_keys.len() == signatures.len(), ErrorCode::KeyArrayMismatch);
+        require!(public_keys.len() == recovery_ids.len(), ErrorCode::KeyArrayMismatch);
 
         // Verify all key ownership proofs
         // For initial registration, use owner pubkey + DID as message (nonce is not yet initialized)
@@ -58,11 +80,35 @@ pub mod sage_registry {
         ]
         .concat();
 
+        let key_count = public_keys.len();
+        let mut key_parity = vec![false; key_count];
+        let mut ed25519_entry_index: u8 = 0;
         for i in 0..public_keys.len() {
-            let key_type = key_types[i];
-            require!(key_type == 0, ErrorCode::UnsupportedKeyType); // Only Ed25519 supported on Solana
-
-            verify_ed25519_signature(&public_keys[i], &message, &signatures[i])?;
+            match key_types[i] {
+                KEY_TYPE_ED25519 => {
+                    verify_ed25519_precompile(
+                        &ctx.accounts.instructions,
+                        ed25519_entry_index,
+                        &public_keys[i],
+                        &message,
+                    )?;
+                    ed25519_entry_index += 1;
+                }
+                KEY_TYPE_SECP256K1 => {
+                    key_parity[i] =
+                        verify_secp256k1_signature(&public_keys[i], &message, &signatures[i], recovery_ids[i])?;
+                }
+                _ => return err!(ErrorCode::UnsupportedKeyType),
+            }
+
+            invoke_verification_hook(
+                ctx.accounts.registry.verification_hook,
+                ctx.remaining_accounts,
+                &did,
+                &public_keys[i],
+                key_types[i],
+                &message,
+            )?;
         }
 
         let agent = &mut ctx.accounts.agent;
@@ -80,14 +126,12 @@ pub mod sage_registry {
         agent.updated_at = clock.unix_timestamp;
         agent.active = true;
         agent.nonce = 0;
-        agent.key_count = public_keys.len() as u8;
 
         // Store keys
-        for i in 0..public_keys.len() {
-            agent.public_keys[i] = public_keys[i];
-            agent.key_types[i] = key_types[i];
-            agent.key_revoked[i] = false;
-        }
+        agent.key_revoked = vec![false; key_count];
+        agent.public_keys = public_keys;
+        agent.key_types = key_types;
+        agent.key_parity = key_parity;
 
         registry.agent_count += 1;
 
@@ -100,17 +144,22 @@ pub mod sage_registry {
         Ok(())
     }
 
-    /// Add a new key to an existing agent
+    /// Add a new key to an existing agent, growing the account to make room
+    /// for it (see [`AddKey`])
     pub fn add_key(
-        ctx: Context<UpdateAgent>,
+        ctx: Context<AddKey>,
         public_key: [u8; 32],
         key_type: u8,
         signature: [u8; 64],
+        recovery_id: u8,
     ) -> Result<()> {
+        let max_keys = ctx.accounts.registry.max_keys_per_agent as usize;
         let agent = &mut ctx.accounts.agent;
 
-        require!(agent.key_count < MAX_KEYS_PER_AGENT as u8, ErrorCode::TooManyKeys);
-        require!(key_type == 0, ErrorCode::UnsupportedKeyType); // Only Ed25519 on Solana
+        // As in `register_agent`, the cap is the registry's configured
+        // `max_keys_per_agent` rather than a compile-time constant; `AddKey`
+        // grows the account by one key entry via `realloc` to make room.
+        require!(agent.public_keys.len() < max_keys, ErrorCode::TooManyKeys);
 
         // Verify key ownership proof
         let message = [
@@ -119,14 +168,30 @@ pub mod sage_registry {
         ]
         .concat();
 
-        verify_ed25519_signature(&public_key, &message, &signature)?;
+        let parity = match key_type {
+            KEY_TYPE_ED25519 => {
+                verify_ed25519_precompile(&ctx.accounts.instructions, 0, &public_key, &message)?;
+                false
+            }
+            KEY_TYPE_SECP256K1 => verify_secp256k1_signature(&public_key, &message, &signature, recovery_id)?,
+            _ => return err!(ErrorCode::UnsupportedKeyType),
+        };
+
+        invoke_verification_hook(
+            ctx.accounts.registry.verification_hook,
+            ctx.remaining_accounts,
+            &agent.did,
+            &public_key,
+            key_type,
+            &message,
+        )?;
 
         // Add key
-        let idx = agent.key_count as usize;
-        agent.public_keys[idx] = public_key;
-        agent.key_types[idx] = key_type;
-        agent.key_revoked[idx] = false;
-        agent.key_count += 1;
+        agent.public_keys.push(public_key);
+        agent.key_types.push(key_type);
+        agent.key_revoked.push(false);
+        agent.key_parity.push(parity);
+        let idx = agent.public_keys.len() - 1;
         agent.nonce += 1;
         agent.updated_at = Clock::get()?.unix_timestamp;
 
@@ -146,13 +211,11 @@ pub mod sage_registry {
     ) -> Result<()> {
         let agent = &mut ctx.accounts.agent;
 
-        require!(key_index < agent.key_count, ErrorCode::InvalidKeyIndex);
+        require!((key_index as usize) < agent.public_keys.len(), ErrorCode::InvalidKeyIndex);
         require!(!agent.key_revoked[key_index as usize], ErrorCode::KeyAlreadyRevoked);
 
         // Count active keys
-        let active_keys = (0..agent.key_count)
-            .filter(|&i| !agent.key_revoked[i as usize])
-            .count();
+        let active_keys = agent.key_revoked.iter().filter(|revoked| !**revoked).count();
 
         require!(active_keys > 1, ErrorCode::CannotRevokeLastKey);
 
@@ -172,17 +235,17 @@ pub mod sage_registry {
 
     /// Rotate a key atomically
     pub fn rotate_key(
-        ctx: Context<UpdateAgent>,
+        ctx: Context<UpdateAgentWithHook>,
         old_key_index: u8,
         new_public_key: [u8; 32],
         new_key_type: u8,
         signature: [u8; 64],
+        recovery_id: u8,
     ) -> Result<()> {
         let agent = &mut ctx.accounts.agent;
 
-        require!(old_key_index < agent.key_count, ErrorCode::InvalidKeyIndex);
+        require!((old_key_index as usize) < agent.public_keys.len(), ErrorCode::InvalidKeyIndex);
         require!(!agent.key_revoked[old_key_index as usize], ErrorCode::KeyAlreadyRevoked);
-        require!(new_key_type == 0, ErrorCode::UnsupportedKeyType);
 
         // Verify new key ownership
         let message = [
@@ -191,11 +254,30 @@ pub mod sage_registry {
         ]
         .concat();
 
-        verify_ed25519_signature(&new_public_key, &message, &signature)?;
+        let parity = match new_key_type {
+            KEY_TYPE_ED25519 => {
+                verify_ed25519_precompile(&ctx.accounts.instructions, 0, &new_public_key, &message)?;
+                false
+            }
+            KEY_TYPE_SECP256K1 => {
+                verify_secp256k1_signature(&new_public_key, &message, &signature, recovery_id)?
+            }
+            _ => return err!(ErrorCode::UnsupportedKeyType),
+        };
+
+        invoke_verification_hook(
+            ctx.accounts.registry.verification_hook,
+            ctx.remaining_accounts,
+            &agent.did,
+            &new_public_key,
+            new_key_type,
+            &message,
+        )?;
 
         // Atomically replace the key
         agent.public_keys[old_key_index as usize] = new_public_key;
         agent.key_types[old_key_index as usize] = new_key_type;
+        agent.key_parity[old_key_index as usize] = parity;
         agent.nonce += 1;
         agent.updated_at = Clock::get()?.unix_timestamp;
 
@@ -208,30 +290,36 @@ pub mod sage_registry {
         Ok(())
     }
 
-    /// Update agent metadata
+    /// Update agent metadata, resizing the account to fit the new content
+    /// (see [`UpdateAgentMetadata`])
     pub fn update_agent(
-        ctx: Context<UpdateAgent>,
+        ctx: Context<UpdateAgentMetadata>,
         name: Option<String>,
         description: Option<String>,
         endpoint: Option<String>,
         capabilities: Option<String>,
     ) -> Result<()> {
+        let limits = &ctx.accounts.registry;
+        let max_name_len = limits.max_name_len as usize;
+        let max_description_len = limits.max_description_len as usize;
+        let max_endpoint_len = limits.max_endpoint_len as usize;
+        let max_capabilities_len = limits.max_capabilities_len as usize;
         let agent = &mut ctx.accounts.agent;
 
         if let Some(n) = name {
-            require!(n.len() <= MAX_NAME_LEN, ErrorCode::NameTooLong);
+            require!(n.len() <= max_name_len, ErrorCode::NameTooLong);
             agent.name = n;
         }
         if let Some(d) = description {
-            require!(d.len() <= MAX_DESCRIPTION_LEN, ErrorCode::DescriptionTooLong);
+            require!(d.len() <= max_description_len, ErrorCode::DescriptionTooLong);
             agent.description = d;
         }
         if let Some(e) = endpoint {
-            require!(e.len() <= MAX_ENDPOINT_LEN, ErrorCode::EndpointTooLong);
+            require!(e.len() <= max_endpoint_len, ErrorCode::EndpointTooLong);
             agent.endpoint = e;
         }
         if let Some(c) = capabilities {
-            require!(c.len() <= MAX_CAPABILITIES_LEN, ErrorCode::CapabilitiesTooLong);
+            require!(c.len() <= max_capabilities_len, ErrorCode::CapabilitiesTooLong);
             agent.capabilities = c;
         }
 
@@ -276,6 +364,38 @@ pub mod sage_registry {
 
         Ok(())
     }
+
+    /// Raise or lower the per-agent metadata/key limits that `register_agent`
+    /// and `update_agent` validate against, without a program upgrade.
+    pub fn set_registry_limits(
+        ctx: Context<SetLimits>,
+        max_did_len: u16,
+        max_name_len: u16,
+        max_description_len: u16,
+        max_endpoint_len: u16,
+        max_capabilities_len: u16,
+        max_keys_per_agent: u8,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.max_did_len = max_did_len;
+        registry.max_name_len = max_name_len;
+        registry.max_description_len = max_description_len;
+        registry.max_endpoint_len = max_endpoint_len;
+        registry.max_capabilities_len = max_capabilities_len;
+        registry.max_keys_per_agent = max_keys_per_agent;
+
+        emit!(RegistryLimitsUpdated {
+            max_did_len,
+            max_name_len,
+            max_description_len,
+            max_endpoint_len,
+            max_capabilities_len,
+            max_keys_per_agent,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -283,7 +403,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + Registry::LEN,
+        space = 8 + Registry::INIT_SPACE,
         seeds = [b"registry"],
         bump
     )]
@@ -294,12 +414,19 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(did: String)]
+#[instruction(did: String, name: String, description: String, endpoint: String, capabilities: String, public_keys: Vec<[u8; 32]>)]
 pub struct RegisterAgent<'info> {
     #[account(
         init,
         payer = owner,
-        space = 8 + Agent::LEN,
+        space = Agent::space_for(
+            did.as_bytes().len(),
+            name.as_bytes().len(),
+            description.as_bytes().len(),
+            endpoint.as_bytes().len(),
+            capabilities.as_bytes().len(),
+            public_keys.len(),
+        ),
         seeds = [b"agent", did.as_bytes()],
         bump
     )]
@@ -313,8 +440,15 @@ pub struct RegisterAgent<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
+    /// The Instructions sysvar, introspected to find the Ed25519 precompile
+    /// instruction carrying the registration's key-ownership proofs.
+    /// CHECK: address constraint pins this to the real sysvar.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
 }
 
+/// Used by instructions that touch an agent without changing its on-chain
+/// size: revocation and deactivation.
 #[derive(Accounts)]
 pub struct UpdateAgent<'info> {
     #[account(
@@ -327,6 +461,96 @@ pub struct UpdateAgent<'info> {
     pub owner: Signer<'info>,
 }
 
+/// Like [`UpdateAgent`], but also carries `registry` so the instruction can
+/// read `verification_hook` and invoke it. Used by `rotate_key`, which
+/// replaces a key entry in place without changing the account's size;
+/// `add_key` needs [`AddKey`] instead since it also grows the account.
+#[derive(Accounts)]
+pub struct UpdateAgentWithHook<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.did.as_bytes()],
+        bump,
+        has_one = owner
+    )]
+    pub agent: Account<'info, Agent>,
+    #[account(
+        seeds = [b"registry"],
+        bump
+    )]
+    pub registry: Account<'info, Registry>,
+    pub owner: Signer<'info>,
+    /// CHECK: address constraint pins this to the real Instructions sysvar.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+/// Used by `add_key`: carries `registry` for the verification hook, and
+/// grows `agent` by exactly one key entry's worth of space (well under
+/// `MAX_PERMITTED_DATA_INCREASE`, so this never needs to happen over
+/// multiple instructions), funded by `owner`.
+#[derive(Accounts)]
+pub struct AddKey<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.did.as_bytes()],
+        bump,
+        has_one = owner,
+        realloc = Agent::space_for(
+            agent.did.len(),
+            agent.name.len(),
+            agent.description.len(),
+            agent.endpoint.len(),
+            agent.capabilities.len(),
+            agent.public_keys.len() + 1,
+        ),
+        realloc::payer = owner,
+        realloc::zero = true,
+    )]
+    pub agent: Account<'info, Agent>,
+    #[account(
+        seeds = [b"registry"],
+        bump
+    )]
+    pub registry: Account<'info, Registry>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: address constraint pins this to the real Instructions sysvar.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+/// Used by `update_agent`: metadata strings are sized to actual content
+/// rather than the worst-case `MAX_*_LEN`, so changing them can grow or
+/// shrink the account; `owner` funds growth.
+#[derive(Accounts)]
+#[instruction(name: Option<String>, description: Option<String>, endpoint: Option<String>, capabilities: Option<String>)]
+pub struct UpdateAgentMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.did.as_bytes()],
+        bump,
+        has_one = owner,
+        realloc = Agent::space_for(
+            agent.did.len(),
+            name.as_ref().map(|s| s.len()).unwrap_or(agent.name.len()),
+            description.as_ref().map(|s| s.len()).unwrap_or(agent.description.len()),
+            endpoint.as_ref().map(|s| s.len()).unwrap_or(agent.endpoint.len()),
+            capabilities.as_ref().map(|s| s.len()).unwrap_or(agent.capabilities.len()),
+            agent.public_keys.len(),
+        ),
+        realloc::payer = owner,
+        realloc::zero = true,
+    )]
+    pub agent: Account<'info, Agent>,
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct SetHook<'info> {
     #[account(
@@ -339,51 +563,118 @@ pub struct SetHook<'info> {
     pub authority: Signer<'info>,
 }
 
+/// Used by `set_registry_limits`; identical shape to [`SetHook`], kept as a
+/// separate type since the two instructions' accounts may diverge later.
+#[derive(Accounts)]
+pub struct SetLimits<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump,
+        has_one = authority
+    )]
+    pub registry: Account<'info, Registry>,
+    pub authority: Signer<'info>,
+}
+
 #[account]
+#[derive(InitSpace)]
 pub struct Registry {
     pub authority: Pubkey,
     pub agent_count: u64,
     pub verification_hook: Option<Pubkey>,
+    /// Per-agent limits below, settable by `authority` via
+    /// `set_registry_limits` so deployments can raise the
+    /// compile-time-seeded defaults (`MAX_DID_LEN` etc.) without a program
+    /// upgrade. `register_agent`/`update_agent`/`add_key` validate against
+    /// these fields, not the constants.
+    pub max_did_len: u16,
+    pub max_name_len: u16,
+    pub max_description_len: u16,
+    pub max_endpoint_len: u16,
+    pub max_capabilities_len: u16,
+    pub max_keys_per_agent: u8,
 }
 
-impl Registry {
-    pub const LEN: usize = 32 + 8 + 1 + 32;
-}
-
+/// Agent's variable-length fields are sized to their *actual* content via
+/// [`Agent::space_for`], not a fixed worst case, so growing/shrinking them
+/// (`register_agent`, `add_key`, `update_agent`) only pays rent for what's
+/// stored. `#[derive(InitSpace)]` is still applied here, with `#[max_len]`
+/// pinned to the compile-time defaults, purely to keep a derived
+/// `Agent::INIT_SPACE` around as a documented worst-case upper bound (e.g.
+/// for clients estimating rent); no `space`/`realloc` constraint in this
+/// file uses it, and it understates the true worst case once
+/// `set_registry_limits` raises a limit past its compile-time default.
 #[account]
+#[derive(InitSpace)]
 pub struct Agent {
+    #[max_len(MAX_DID_LEN)]
     pub did: String,
+    #[max_len(MAX_NAME_LEN)]
     pub name: String,
+    #[max_len(MAX_DESCRIPTION_LEN)]
     pub description: String,
+    #[max_len(MAX_ENDPOINT_LEN)]
     pub endpoint: String,
+    #[max_len(MAX_CAPABILITIES_LEN)]
     pub capabilities: String,
     pub owner: Pubkey,
     pub registered_at: i64,
     pub updated_at: i64,
     pub active: bool,
     pub nonce: u64,
-    pub key_count: u8,
-    pub public_keys: [[u8; 32]; MAX_KEYS_PER_AGENT],
-    pub key_types: [u8; MAX_KEYS_PER_AGENT],
-    pub key_revoked: [bool; MAX_KEYS_PER_AGENT],
+    #[max_len(MAX_KEYS_PER_AGENT)]
+    pub public_keys: Vec<[u8; 32]>,
+    #[max_len(MAX_KEYS_PER_AGENT)]
+    pub key_types: Vec<u8>,
+    #[max_len(MAX_KEYS_PER_AGENT)]
+    pub key_revoked: Vec<bool>,
+    /// For secp256k1 keys, the parity (oddness) of the public key's Y
+    /// coordinate, since only the 32-byte X coordinate fits in `public_keys`.
+    /// Unused (`false`) for Ed25519 keys.
+    #[max_len(MAX_KEYS_PER_AGENT)]
+    pub key_parity: Vec<bool>,
 }
 
 impl Agent {
-    pub const LEN: usize =
-        4 + MAX_DID_LEN +
-        4 + MAX_NAME_LEN +
-        4 + MAX_DESCRIPTION_LEN +
-        4 + MAX_ENDPOINT_LEN +
-        4 + MAX_CAPABILITIES_LEN +
+    /// Size of the fields whose width never depends on content length.
+    const FIXED_LEN: usize =
         32 + // owner
         8 +  // registered_at
         8 +  // updated_at
         1 +  // active
-        8 +  // nonce
-        1 +  // key_count
-        (32 * MAX_KEYS_PER_AGENT) + // public_keys
-        MAX_KEYS_PER_AGENT +         // key_types
-        MAX_KEYS_PER_AGENT;          // key_revoked
+        8;   // nonce
+
+    /// Borsh-serialized width of one entry across the four parallel key
+    /// vectors (`public_keys`, `key_types`, `key_revoked`, `key_parity`).
+    const KEY_ENTRY_LEN: usize = 32 + 1 + 1 + 1;
+
+    /// Total account space, including the 8-byte Anchor discriminator,
+    /// needed to hold the given content lengths. Unlike the old hand-summed
+    /// `LEN` constant (which over-provisioned every account for the
+    /// worst-case `MAX_*_LEN` strings and a full `MAX_KEYS_PER_AGENT` set of
+    /// keys), this lets `init`/`realloc` size an account for what it
+    /// actually holds.
+    fn space_for(
+        did_len: usize,
+        name_len: usize,
+        description_len: usize,
+        endpoint_len: usize,
+        capabilities_len: usize,
+        key_count: usize,
+    ) -> usize {
+        8 + // discriminator
+        Self::FIXED_LEN +
+        4 + did_len +
+        4 + name_len +
+        4 + description_len +
+        4 + endpoint_len +
+        4 + capabilities_len +
+        // public_keys, key_types, key_revoked, key_parity each carry their
+        // own Borsh length prefix
+        (4 * 4) +
+        key_count * Self::KEY_ENTRY_LEN
+    }
 }
 
 #[event]
@@ -432,6 +723,17 @@ pub struct HookUpdated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct RegistryLimitsUpdated {
+    pub max_did_len: u16,
+    pub max_name_len: u16,
+    pub max_description_len: u16,
+    pub max_endpoint_len: u16,
+    pub max_capabilities_len: u16,
+    pub max_keys_per_agent: u8,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("DID too long")]
@@ -462,25 +764,192 @@ pub enum ErrorCode {
     KeyAlreadyRevoked,
     #[msg("Cannot revoke last key")]
     CannotRevokeLastKey,
+    #[msg("secp256k1 signature recovery failed")]
+    Secp256k1RecoveryFailed,
+    #[msg("Recovered secp256k1 public key does not match the provided key")]
+    Secp256k1KeyMismatch,
+    #[msg("Verification hook accounts missing, or the first remaining account isn't the configured hook program")]
+    MissingHookAccounts,
+    #[msg("Verification hook rejected the request")]
+    VerificationHookRejected,
+    #[msg("Missing or invalid Ed25519 precompile instruction")]
+    MissingOrInvalidPrecompile,
+}
+
+/// Discriminator for the hook-side instruction every verification hook
+/// program is expected to expose, derived the same way Anchor derives
+/// instruction discriminators (first 8 bytes of
+/// sha256("global:verify_key_registration")), so any Anchor-based hook can
+/// implement it as a single `#[program]` method of that name.
+fn verification_hook_discriminator() -> [u8; 8] {
+    let hash = anchor_lang::solana_program::hash::hash(b"global:verify_key_registration");
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash.to_bytes()[..8]);
+    discriminator
+}
+
+/// Invoke the configured verification hook, if any, aborting the
+/// registration or key change if the hook rejects it.
+///
+/// The registry has no compile-time dependency on a specific hook
+/// implementation, so the hook program and any accounts it needs (e.g. its
+/// own PDA state) are passed through `remaining_accounts`: the first
+/// remaining account must be the hook program itself, followed by whatever
+/// accounts its `verify_key_registration` instruction expects, in order.
+/// Signer/writable flags on the built `AccountMeta`s are propagated
+/// straight from the `AccountInfo`s the caller supplied.
+fn invoke_verification_hook(
+    hook_program: Option<Pubkey>,
+    remaining_accounts: &[AccountInfo],
+    did: &str,
+    public_key: &[u8; 32],
+    key_type: u8,
+    message: &[u8],
+) -> Result<()> {
+    let hook_program_id = match hook_program {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+
+    let (hook_program_account, hook_accounts) = remaining_accounts
+        .split_first()
+        .ok_or(ErrorCode::MissingHookAccounts)?;
+    require_keys_eq!(*hook_program_account.key, hook_program_id, ErrorCode::MissingHookAccounts);
+
+    let mut data = verification_hook_discriminator().to_vec();
+    did.serialize(&mut data)?;
+    public_key.serialize(&mut data)?;
+    key_type.serialize(&mut data)?;
+    message.serialize(&mut data)?;
+
+    let accounts = hook_accounts
+        .iter()
+        .map(|account| {
+            if account.is_writable {
+                AccountMeta::new(*account.key, account.is_signer)
+            } else {
+                AccountMeta::new_readonly(*account.key, account.is_signer)
+            }
+        })
+        .collect();
+
+    let ix = Instruction {
+        program_id: hook_program_id,
+        accounts,
+        data,
+    };
+
+    // `invoke` needs the callee program's own `AccountInfo` in `account_infos`
+    // (it isn't in `hook_accounts`, which `split_first` stripped it out of
+    // above) even though it must stay out of the instruction's `AccountMeta`
+    // list, so pass the full `remaining_accounts` here.
+    invoke(&ix, remaining_accounts).map_err(|_| error!(ErrorCode::VerificationHookRejected))?;
+
+    Ok(())
 }
 
-/// Verify Ed25519 signature using ed25519-dalek
-fn verify_ed25519_signature(
+/// Verify an Ed25519 ownership proof via Solana's native Ed25519 precompile
+/// instead of running Curve25519 math in-program.
+///
+/// The caller is expected to have prepended an `Ed25519SigVerify111...`
+/// precompile instruction carrying `(pubkey, message, signature)` tuples to
+/// this same transaction, immediately before the instruction invoking this
+/// program; the runtime verifies the signature cryptographically before our
+/// program even starts executing. All we need to do here is load that
+/// instruction back out of the Instructions sysvar and confirm the `index`th
+/// entry in it declares the exact `pubkey`/`message` we expect — this rules
+/// out a caller attaching a precompile instruction that verifies a
+/// *different* key or message than the one it's claiming ownership of here.
+fn verify_ed25519_precompile(
+    instructions_sysvar: &AccountInfo,
+    entry_index: u8,
     pubkey: &[u8; 32],
     message: &[u8],
-    signature: &[u8; 64],
 ) -> Result<()> {
-    // Convert public key bytes to VerifyingKey
-    let verifying_key = VerifyingKey::from_bytes(pubkey)
-        .map_err(|_| ErrorCode::InvalidSignature)?;
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, ErrorCode::MissingOrInvalidPrecompile);
 
-    // Convert signature bytes to Signature
-    let sig = Signature::from_bytes(signature);
+    let precompile_ix = load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)
+        .map_err(|_| error!(ErrorCode::MissingOrInvalidPrecompile))?;
+    require_keys_eq!(
+        precompile_ix.program_id,
+        ed25519_program::ID,
+        ErrorCode::MissingOrInvalidPrecompile
+    );
 
-    // Verify the signature
-    verifying_key
-        .verify(message, &sig)
-        .map_err(|_| ErrorCode::InvalidSignature)?;
+    let (declared_pubkey, declared_message) = read_ed25519_entry(&precompile_ix.data, entry_index)?;
+    require!(declared_pubkey.as_slice() == pubkey.as_slice(), ErrorCode::MissingOrInvalidPrecompile);
+    require!(declared_message.as_slice() == message, ErrorCode::MissingOrInvalidPrecompile);
 
     Ok(())
 }
+
+/// Parse the `entry_index`th signature entry out of an Ed25519 precompile
+/// instruction's data, returning `(public_key, message)`.
+///
+/// Layout (see the Ed25519 native program): a `u8` entry count, one padding
+/// byte, then `count` 14-byte headers of little-endian `u16` offsets
+/// (`signature_offset`, `signature_instruction_index`, `public_key_offset`,
+/// `public_key_instruction_index`, `message_data_offset`,
+/// `message_data_size`, `message_instruction_index`), followed by the actual
+/// signature/pubkey/message bytes the offsets point into. We only accept
+/// entries whose three `*_instruction_index` fields are `u16::MAX` ("this
+/// instruction"), since that's what a precompile instruction built
+/// alongside ours will always use.
+fn read_ed25519_entry(data: &[u8], entry_index: u8) -> Result<(Vec<u8>, Vec<u8>)> {
+    require!(data.len() >= 2, ErrorCode::MissingOrInvalidPrecompile);
+    let count = data[0];
+    require!((entry_index as usize) < count as usize, ErrorCode::MissingOrInvalidPrecompile);
+
+    let header_start = 2 + (entry_index as usize) * 14;
+    require!(data.len() >= header_start + 14, ErrorCode::MissingOrInvalidPrecompile);
+    let header = &data[header_start..header_start + 14];
+    let read_u16 = |offset: usize| u16::from_le_bytes([header[offset], header[offset + 1]]);
+
+    let signature_instruction_index = read_u16(2);
+    let public_key_offset = read_u16(4) as usize;
+    let public_key_instruction_index = read_u16(6);
+    let message_data_offset = read_u16(8) as usize;
+    let message_data_size = read_u16(10) as usize;
+    let message_instruction_index = read_u16(12);
+
+    require!(
+        signature_instruction_index == u16::MAX
+            && public_key_instruction_index == u16::MAX
+            && message_instruction_index == u16::MAX,
+        ErrorCode::MissingOrInvalidPrecompile
+    );
+    require!(
+        data.len() >= public_key_offset + 32 && data.len() >= message_data_offset + message_data_size,
+        ErrorCode::MissingOrInvalidPrecompile
+    );
+
+    let pubkey = data[public_key_offset..public_key_offset + 32].to_vec();
+    let message = data[message_data_offset..message_data_offset + message_data_size].to_vec();
+    Ok((pubkey, message))
+}
+
+/// Verify a compact (64-byte) ECDSA secp256k1 signature as an ownership
+/// proof, recovering the signer's public key via the secp256k1_recover
+/// syscall and checking it matches `pubkey_x`.
+///
+/// Returns the parity (oddness) of the recovered key's Y coordinate, to be
+/// stored alongside `pubkey_x` since only the X coordinate fits in
+/// `Agent::public_keys`.
+fn verify_secp256k1_signature(
+    pubkey_x: &[u8; 32],
+    message: &[u8],
+    signature: &[u8; 64],
+    recovery_id: u8,
+) -> Result<bool> {
+    let message_hash = keccak::hash(message);
+
+    let recovered = secp256k1_recover(message_hash.as_ref(), recovery_id, signature)
+        .map_err(|_| ErrorCode::Secp256k1RecoveryFailed)?;
+    let recovered_bytes = recovered.to_bytes(); // 64 bytes: X (32) || Y (32)
+
+    require!(&recovered_bytes[..32] == pubkey_x, ErrorCode::Secp256k1KeyMismatch);
+
+    let y_is_odd = recovered_bytes[63] & 1 == 1;
+    Ok(y_is_odd)
+}
@@ -14,35 +14,6 @@ use anchor_lang::prelude::Pubkey;
 mod registry_tests {
     use super::*;
 
-    #[test]
-    fn test_agent_len_calculation() {
-        // Verify Agent struct size calculation is correct
-        let expected_len =
-            4 + MAX_DID_LEN +           // did
-            4 + MAX_NAME_LEN +          // name
-            4 + MAX_DESCRIPTION_LEN +   // description
-            4 + MAX_ENDPOINT_LEN +      // endpoint
-            4 + MAX_CAPABILITIES_LEN +  // capabilities
-            32 +                         // owner
-            8 +                          // registered_at
-            8 +                          // updated_at
-            1 +                          // active
-            8 +                          // nonce
-            1 +                          // key_count
-            (32 * MAX_KEYS_PER_AGENT) + // public_keys
-            MAX_KEYS_PER_AGENT +        // key_types
-            MAX_KEYS_PER_AGENT;         // key_revoked
-
-        assert_eq!(Agent::LEN, expected_len);
-    }
-
-    #[test]
-    fn test_registry_len_calculation() {
-        // Verify Registry struct size calculation
-        let expected_len = 32 + 8 + 1 + 32; // authority + agent_count + option flag + pubkey
-        assert_eq!(Registry::LEN, expected_len);
-    }
-
     #[test]
     fn test_max_keys_limit() {
         assert_eq!(MAX_KEYS_PER_AGENT, 5);
@@ -75,9 +46,14 @@ mod registry_tests {
             ErrorCode::InvalidKeyIndex,
             ErrorCode::KeyAlreadyRevoked,
             ErrorCode::CannotRevokeLastKey,
+            ErrorCode::Secp256k1RecoveryFailed,
+            ErrorCode::Secp256k1KeyMismatch,
+            ErrorCode::MissingHookAccounts,
+            ErrorCode::VerificationHookRejected,
+            ErrorCode::MissingOrInvalidPrecompile,
         ];
 
-        assert_eq!(errors.len(), 14);
+        assert_eq!(errors.len(), 19);
     }
 
     #[test]
@@ -108,14 +84,17 @@ mod registry_tests {
 
     #[test]
     fn test_key_type_validation() {
-        // Key type 0 = Ed25519 (only supported on Solana)
-        let valid_key_type = 0u8;
-        assert_eq!(valid_key_type, 0);
+        use sage_registry::{KEY_TYPE_ED25519, KEY_TYPE_SECP256K1};
+
+        // Key type 0 = Ed25519, key type 1 = secp256k1; both are supported.
+        assert_eq!(KEY_TYPE_ED25519, 0);
+        assert_eq!(KEY_TYPE_SECP256K1, 1);
 
-        // Other key types should be rejected
-        let invalid_key_types = vec![1u8, 2u8, 3u8, 255u8];
+        // Anything else should be rejected.
+        let invalid_key_types = vec![2u8, 3u8, 255u8];
         for kt in invalid_key_types {
-            assert_ne!(kt, 0, "Key type {} should be invalid", kt);
+            assert_ne!(kt, KEY_TYPE_ED25519, "Key type {} should be invalid", kt);
+            assert_ne!(kt, KEY_TYPE_SECP256K1, "Key type {} should be invalid", kt);
         }
     }
 
@@ -142,14 +121,14 @@ mod registry_tests {
             updated_at: 0,
             active: true,
             nonce: 0,
-            key_count: 0,
-            public_keys: [[0u8; 32]; MAX_KEYS_PER_AGENT],
-            key_types: [0u8; MAX_KEYS_PER_AGENT],
-            key_revoked: [false; MAX_KEYS_PER_AGENT],
+            public_keys: Vec::new(),
+            key_types: Vec::new(),
+            key_revoked: Vec::new(),
+            key_parity: Vec::new(),
         };
 
         assert_eq!(agent.nonce, 0);
-        assert_eq!(agent.key_count, 0);
+        assert!(agent.public_keys.is_empty());
         assert!(agent.active);
         assert_eq!(agent.owner, owner);
     }
@@ -161,11 +140,18 @@ mod registry_tests {
             authority,
             agent_count: 0,
             verification_hook: None,
+            max_did_len: MAX_DID_LEN as u16,
+            max_name_len: MAX_NAME_LEN as u16,
+            max_description_len: MAX_DESCRIPTION_LEN as u16,
+            max_endpoint_len: MAX_ENDPOINT_LEN as u16,
+            max_capabilities_len: MAX_CAPABILITIES_LEN as u16,
+            max_keys_per_agent: MAX_KEYS_PER_AGENT as u8,
         };
 
         assert_eq!(registry.authority, authority);
         assert_eq!(registry.agent_count, 0);
         assert!(registry.verification_hook.is_none());
+        assert_eq!(registry.max_keys_per_agent, MAX_KEYS_PER_AGENT as u8);
     }
 
     #[test]
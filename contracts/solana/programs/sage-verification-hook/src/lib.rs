@@ -75,6 +75,56 @@ pub mod sage_verification_hook {
         Ok(())
     }
 
+    /// The instruction `sage-registry` actually CPIs into for its
+    /// configurable verification hook (see `invoke_verification_hook` and
+    /// `verification_hook_discriminator` there): applies this hook's
+    /// blacklist/cooldown/daily-limit policy to a key being registered,
+    /// rotated, or added. The registry has already verified `public_key`'s
+    /// ownership of `message` itself (via the Ed25519 precompile or
+    /// secp256k1 recovery) before making this CPI, so unlike
+    /// `verify_registration` this does not re-verify a signature — it only
+    /// has `public_key`/`key_type`/`message` in scope for a hook that wants
+    /// to apply key-specific policy.
+    pub fn verify_key_registration(
+        ctx: Context<VerifyKeyRegistration>,
+        did: String,
+        _public_key: [u8; 32],
+        _key_type: u8,
+        _message: Vec<u8>,
+    ) -> Result<()> {
+        let user_state = &mut ctx.accounts.user_state;
+        let clock = Clock::get()?;
+
+        // Check if user is blacklisted
+        require!(!user_state.blacklisted, ErrorCode::Blacklisted);
+
+        // Check cooldown
+        if user_state.last_registration > 0 {
+            require!(
+                clock.unix_timestamp >= user_state.last_registration + REGISTRATION_COOLDOWN,
+                ErrorCode::CooldownActive
+            );
+        }
+
+        // Check daily limit
+        let current_day = clock.unix_timestamp / 86400;
+        if user_state.last_day != current_day {
+            user_state.registration_count = 0;
+            user_state.last_day = current_day;
+        }
+
+        require!(
+            user_state.registration_count < MAX_REGISTRATIONS_PER_DAY,
+            ErrorCode::DailyLimitReached
+        );
+
+        // Verify DID format
+        require!(did.starts_with("did:"), ErrorCode::InvalidDIDFormat);
+        require!(did.len() >= 10, ErrorCode::InvalidDIDFormat);
+
+        Ok(())
+    }
+
     /// Update user state after registration
     pub fn after_registration(ctx: Context<AfterRegistration>) -> Result<()> {
         let user_state = &mut ctx.accounts.user_state;
@@ -171,6 +221,27 @@ pub struct VerifyRegistration<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// Accounts for `verify_key_registration`, in the order `sage-registry`'s
+/// `invoke_verification_hook` forwards them via `remaining_accounts` (right
+/// after the hook program account itself, which isn't part of this list).
+#[derive(Accounts)]
+#[instruction(did: String)]
+pub struct VerifyKeyRegistration<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_state", signer.key().as_ref()],
+        bump,
+    )]
+    pub user_state: Account<'info, UserState>,
+    #[account(
+        seeds = [b"hook_state"],
+        bump,
+        constraint = hook_state.enabled @ ErrorCode::HookDisabled
+    )]
+    pub hook_state: Account<'info, HookState>,
+    pub signer: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct AfterRegistration<'info> {
     #[account(
@@ -0,0 +1,147 @@
+//! Deterministic ("brain wallet") key derivation and vanity-address search
+//!
+//! Lets an operator recover an agent's Ed25519/X25519 key pair from a
+//! human-memorable passphrase instead of storing raw secret bytes, and find
+//! a passphrase nonce whose derived `did:sage:<network>:<address>` starts
+//! with a chosen hex prefix.
+
+use crate::crypto::Crypto;
+use crate::did::{Did, DidDocument};
+use crate::error::{Error, Result};
+use crate::types::{KeyPair, KeyType, SecretBytes};
+use argon2::{Algorithm, Argon2, Params, Version};
+use ed25519_dalek::{PublicKey as Ed25519PublicKey, SecretKey};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// Domain-separation salt for the Ed25519 signing key derivation.
+const DID_SALT: &[u8] = b"sage-did-v1";
+/// Distinct info string for the X25519 KEM key so it never collides with
+/// the signing key, even though both come from the same passphrase.
+const KEM_INFO: &[u8] = b"sage-did-v1-kem";
+
+/// Argon2id parameters for the passphrase KDF (19 MiB, 2 passes, 1 lane).
+fn argon2_params() -> Params {
+    Params::new(19_456, 2, 1, Some(32)).expect("static argon2 params are valid")
+}
+
+fn argon2_derive(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params());
+    let mut out = [0u8; 32];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), salt, &mut out)
+        .map_err(|e| Error::Crypto(format!("argon2 derivation failed: {}", e)))?;
+    Ok(out)
+}
+
+/// Deterministically derive an agent's Ed25519 signing key pair from a
+/// passphrase.
+pub fn derive_ed25519(passphrase: &str) -> Result<KeyPair> {
+    let seed = argon2_derive(passphrase, DID_SALT)?;
+    let secret = SecretKey::from_bytes(&seed)
+        .map_err(|e| Error::Crypto(format!("Invalid secret key: {}", e)))?;
+    let public = Ed25519PublicKey::from(&secret);
+    Ok(KeyPair {
+        private_key: SecretBytes::new(secret.to_bytes().to_vec()),
+        public_key: public.to_bytes().to_vec(),
+        key_type: KeyType::Ed25519,
+    })
+}
+
+/// Deterministically derive an agent's X25519 KEM key pair from the same
+/// passphrase, via a second KDF pass with a distinct info string.
+pub fn derive_x25519(passphrase: &str) -> Result<KeyPair> {
+    let seed = argon2_derive(passphrase, KEM_INFO)?;
+    let secret = StaticSecret::from(seed);
+    let public = PublicKey::from(&secret);
+    Ok(KeyPair {
+        private_key: SecretBytes::new(secret.to_bytes().to_vec()),
+        public_key: public.to_bytes().to_vec(),
+        key_type: KeyType::X25519,
+    })
+}
+
+/// Derive the on-chain `address` portion of a DID from an Ed25519 public key:
+/// the first 20 bytes of its SHA-256 hash, hex-encoded.
+pub(crate) fn derive_address(ed25519_public_key: &[u8]) -> String {
+    let digest = Crypto::hash_sha256(ed25519_public_key);
+    format!("0x{}", hex::encode(&digest[..20]))
+}
+
+impl Did {
+    /// Derive a `Did` deterministically from a passphrase.
+    pub fn from_passphrase(passphrase: &str, network: &str) -> Result<Self> {
+        let identity = derive_ed25519(passphrase)?;
+        Ok(Did::from_parts(network, &derive_address(&identity.public_key)))
+    }
+
+    /// Search for a nonce such that appending `#<nonce>` to `passphrase`
+    /// derives a `Did` whose address starts with `prefix` (case-insensitive).
+    ///
+    /// Returns the winning nonce alongside the derived `Did`, so recovery is
+    /// reproducible: deriving from `"{passphrase}#{nonce}"` again yields the
+    /// same identity.
+    pub fn vanity(passphrase: &str, network: &str, prefix: &str) -> Result<(u64, Self)> {
+        let prefix_lower = prefix.trim_start_matches("0x").to_lowercase();
+        let mut nonce: u64 = 0;
+        loop {
+            let candidate = format!("{}#{}", passphrase, nonce);
+            let did = Did::from_passphrase(&candidate, network)?;
+            let hex_part = did.address.trim_start_matches("0x").to_lowercase();
+            if hex_part.starts_with(&prefix_lower) {
+                return Ok((nonce, did));
+            }
+            nonce = nonce
+                .checked_add(1)
+                .ok_or_else(|| Error::Other("vanity search exhausted nonce space".to_string()))?;
+        }
+    }
+}
+
+impl DidDocument {
+    /// Derive a full `DidDocument` (identity + KEM keys, address) from a
+    /// passphrase, so an operator can recover it without storing raw secret
+    /// bytes.
+    pub fn from_passphrase(passphrase: &str, network: &str) -> Result<Self> {
+        let identity = derive_ed25519(passphrase)?;
+        let kem = derive_x25519(passphrase)?;
+        let address = derive_address(&identity.public_key);
+        let did = Did::from_parts(network, &address);
+
+        Ok(DidDocument {
+            did,
+            public_key: identity.public_key,
+            public_kem_key: kem.public_key,
+            owner_address: address,
+            is_active: true,
+            revoked: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derivation_is_deterministic() {
+        let a = derive_ed25519("correct horse battery staple").unwrap();
+        let b = derive_ed25519("correct horse battery staple").unwrap();
+        assert_eq!(a.private_key, b.private_key);
+        assert_eq!(a.public_key, b.public_key);
+    }
+
+    #[test]
+    fn signing_and_kem_keys_differ() {
+        let identity = derive_ed25519("agent-passphrase").unwrap();
+        let kem = derive_x25519("agent-passphrase").unwrap();
+        assert_ne!(identity.private_key, kem.private_key);
+    }
+
+    #[test]
+    fn vanity_search_is_reproducible() {
+        let (nonce, did) = Did::vanity("test-passphrase", "ethereum", "0").unwrap();
+        let replayed = Did::from_passphrase(&format!("test-passphrase#{}", nonce), "ethereum").unwrap();
+        assert_eq!(did, replayed);
+        assert!(did.address.starts_with("0x0"));
+    }
+}
@@ -0,0 +1,517 @@
+//! Self-contained X.509 v3 credential subsystem
+//!
+//! Lets an agent present a portable, offline-verifiable credential binding
+//! its `did:sage:...` identity to its registered Ed25519 key, so a peer that
+//! never touches Solana can still confirm who it's talking to. The
+//! certificate is self-signed by the agent's own key, embeds the registry's
+//! `owner`/`nonce`/registration-timestamp fields in a private SAGE
+//! attestation extension, and a verifier checks the signature against the
+//! on-chain public key and cross-checks the attestation against the
+//! registry's `Agent` account.
+//!
+//! DER is assembled (and parsed back) by hand rather than pulling in a full
+//! ASN.1 crate: the certificate shape needed here is small and fixed — an
+//! Ed25519-signed leaf with one private extension — and hand-rolling it
+//! keeps this crate's dependency footprint where it already is.
+
+use crate::did::Did;
+use crate::error::{Error, Result};
+use crate::types::{KeyPair, KeyType};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ed25519_dalek::{
+    ExpandedSecretKey, PublicKey as Ed25519PublicKey, SecretKey, Signature, Verifier,
+};
+
+/// Private enterprise-arc OID for the SAGE attestation extension:
+/// `1.3.6.1.4.1.61166.1`. 61166 is an unassigned placeholder arc; swap in a
+/// real IANA Private Enterprise Number before using this outside test nets.
+const SAGE_ATTESTATION_OID: &[u64] = &[1, 3, 6, 1, 4, 1, 61_166, 1];
+
+/// `id-Ed25519`, RFC 8410.
+const ED25519_ALG_OID: &[u64] = &[1, 3, 101, 112];
+
+/// `id-ce-keyUsage`, RFC 5280.
+const KEY_USAGE_OID: &[u64] = &[2, 5, 29, 15];
+
+/// `id-at-commonName`, RFC 5280.
+const COMMON_NAME_OID: &[u64] = &[2, 5, 4, 3];
+
+/// Registry fields embedded in the SAGE private extension, so a verifier can
+/// confirm the certificate still matches the on-chain `Agent` account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attestation {
+    /// The agent owner's public key, as stored on-chain.
+    pub owner: [u8; 32],
+    /// The `Agent` account's current key-rotation nonce.
+    pub nonce: u64,
+    /// Unix timestamp (seconds) the agent was registered.
+    pub registered_at: i64,
+}
+
+/// A DER-encoded, self-signed X.509 v3 certificate binding a SAGE DID to its
+/// registered Ed25519 key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentCertificate {
+    der: Vec<u8>,
+}
+
+impl AgentCertificate {
+    /// The raw DER encoding, suitable for writing to a `.der` file or
+    /// sending over the wire.
+    pub fn as_der(&self) -> &[u8] {
+        &self.der
+    }
+
+    /// Wrap an existing DER-encoded certificate for parsing/verification.
+    pub fn from_der(der: Vec<u8>) -> Self {
+        Self { der }
+    }
+}
+
+// ---------------------------------------------------------------------
+// Minimal DER encoding
+// ---------------------------------------------------------------------
+
+fn der_len(len: usize) -> Vec<u8> {
+    if len < 0x80 {
+        vec![len as u8]
+    } else {
+        let bytes = len.to_be_bytes();
+        let trimmed: Vec<u8> = bytes
+            .iter()
+            .skip_while(|&&b| b == 0)
+            .copied()
+            .collect();
+        let mut out = vec![0x80 | trimmed.len() as u8];
+        out.extend_from_slice(&trimmed);
+        out
+    }
+}
+
+fn der_tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    out.extend(der_len(content.len()));
+    out.extend_from_slice(content);
+    out
+}
+
+fn der_sequence(parts: &[Vec<u8>]) -> Vec<u8> {
+    let content: Vec<u8> = parts.iter().flatten().copied().collect();
+    der_tlv(0x30, &content)
+}
+
+fn der_explicit(tag_number: u8, inner: Vec<u8>) -> Vec<u8> {
+    der_tlv(0xA0 | tag_number, &inner)
+}
+
+fn der_oid(arcs: &[u64]) -> Vec<u8> {
+    let mut content = vec![(arcs[0] * 40 + arcs[1]) as u8];
+    for &arc in &arcs[2..] {
+        let mut chunk = vec![(arc & 0x7f) as u8];
+        let mut rem = arc >> 7;
+        while rem > 0 {
+            chunk.push(0x80 | (rem & 0x7f) as u8);
+            rem >>= 7;
+        }
+        chunk.reverse();
+        content.extend(chunk);
+    }
+    der_tlv(0x06, &content)
+}
+
+fn der_integer_u64(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let mut trimmed: Vec<u8> = bytes
+        .iter()
+        .skip_while(|&&b| b == 0)
+        .copied()
+        .collect();
+    if trimmed.is_empty() {
+        trimmed.push(0);
+    } else if trimmed[0] & 0x80 != 0 {
+        trimmed.insert(0, 0);
+    }
+    der_tlv(0x02, &trimmed)
+}
+
+fn der_bit_string(bytes: &[u8]) -> Vec<u8> {
+    let mut content = vec![0u8];
+    content.extend_from_slice(bytes);
+    der_tlv(0x03, &content)
+}
+
+fn der_octet_string(bytes: &[u8]) -> Vec<u8> {
+    der_tlv(0x04, bytes)
+}
+
+fn der_utf8_string(s: &str) -> Vec<u8> {
+    der_tlv(0x0C, s.as_bytes())
+}
+
+fn der_generalized_time(dt: DateTime<Utc>) -> Vec<u8> {
+    der_tlv(0x18, dt.format("%Y%m%d%H%M%SZ").to_string().as_bytes())
+}
+
+fn algorithm_identifier_ed25519() -> Vec<u8> {
+    der_sequence(&[der_oid(ED25519_ALG_OID)])
+}
+
+fn name_with_cn(cn: &str) -> Vec<u8> {
+    let atv = der_sequence(&[der_oid(COMMON_NAME_OID), der_utf8_string(cn)]);
+    let rdn = der_tlv(0x31, &atv); // RelativeDistinguishedName ::= SET OF AttributeTypeAndValue
+    der_sequence(&[rdn]) // Name ::= RDNSequence ::= SEQUENCE OF RelativeDistinguishedName
+}
+
+fn subject_public_key_info(public_key: &[u8]) -> Vec<u8> {
+    der_sequence(&[algorithm_identifier_ed25519(), der_bit_string(public_key)])
+}
+
+fn key_usage_extension() -> Vec<u8> {
+    // KeyUsage ::= BIT STRING; digitalSignature is bit 0, so only the
+    // high bit of the first content byte is set, with 7 unused trailing bits.
+    let ku = der_tlv(0x03, &[7, 0b1000_0000]);
+    der_sequence(&[der_oid(KEY_USAGE_OID), der_octet_string(&ku)])
+}
+
+fn attestation_extension(attestation: &Attestation) -> Vec<u8> {
+    let inner = der_sequence(&[
+        der_octet_string(&attestation.owner),
+        der_integer_u64(attestation.nonce),
+        der_integer_u64(attestation.registered_at as u64),
+    ]);
+    der_sequence(&[der_oid(SAGE_ATTESTATION_OID), der_octet_string(&inner)])
+}
+
+fn extensions(attestation: &Attestation) -> Vec<u8> {
+    let seq = der_sequence(&[key_usage_extension(), attestation_extension(attestation)]);
+    der_explicit(3, seq)
+}
+
+fn tbs_certificate(
+    did: &Did,
+    public_key: &[u8],
+    serial: u64,
+    not_before: DateTime<Utc>,
+    not_after: DateTime<Utc>,
+    attestation: &Attestation,
+) -> Vec<u8> {
+    let version = der_explicit(0, der_integer_u64(2)); // v3
+    let serial_number = der_integer_u64(serial);
+    let signature_alg = algorithm_identifier_ed25519();
+    let name = name_with_cn(did.as_str()); // self-signed: issuer == subject
+    let validity = der_sequence(&[
+        der_generalized_time(not_before),
+        der_generalized_time(not_after),
+    ]);
+    let spki = subject_public_key_info(public_key);
+    let exts = extensions(attestation);
+
+    der_sequence(&[
+        version,
+        serial_number,
+        signature_alg,
+        name.clone(),
+        validity,
+        name,
+        spki,
+        exts,
+    ])
+}
+
+/// Issue a self-signed X.509 v3 certificate for `identity`, binding its DID
+/// to its registered Ed25519 key and embedding the registry's attestation
+/// fields in a private extension.
+pub fn issue_certificate(
+    identity: &KeyPair,
+    did: &Did,
+    attestation: Attestation,
+    validity_days: i64,
+) -> Result<AgentCertificate> {
+    if identity.key_type != KeyType::Ed25519 {
+        return Err(Error::Crypto(
+            "certificate issuance only supports Ed25519 identities".to_string(),
+        ));
+    }
+
+    let not_before = Utc::now();
+    let not_after = not_before + Duration::days(validity_days);
+    let tbs = tbs_certificate(
+        did,
+        &identity.public_key,
+        attestation.nonce,
+        not_before,
+        not_after,
+        &attestation,
+    );
+
+    let seed: [u8; 32] = identity
+        .private_key
+        .as_bytes()
+        .try_into()
+        .map_err(|_| Error::Crypto("Ed25519 seed must be 32 bytes".to_string()))?;
+    let secret = SecretKey::from_bytes(&seed)
+        .map_err(|e| Error::Crypto(format!("invalid Ed25519 secret key: {}", e)))?;
+    let expanded = ExpandedSecretKey::from(&secret);
+    let public = Ed25519PublicKey::from(&secret);
+    let signature = expanded.sign(&tbs, &public);
+
+    let der = der_sequence(&[
+        tbs,
+        algorithm_identifier_ed25519(),
+        der_bit_string(&signature.to_bytes()),
+    ]);
+    Ok(AgentCertificate { der })
+}
+
+// ---------------------------------------------------------------------
+// Minimal DER parsing
+// ---------------------------------------------------------------------
+
+struct Tlv<'a> {
+    tag: u8,
+    content: &'a [u8],
+}
+
+fn read_length(data: &[u8]) -> Result<(usize, &[u8])> {
+    let first = *data
+        .first()
+        .ok_or_else(|| Error::Crypto("truncated DER length".to_string()))?;
+    if first & 0x80 == 0 {
+        Ok((first as usize, &data[1..]))
+    } else {
+        let n = (first & 0x7f) as usize;
+        if data.len() < 1 + n {
+            return Err(Error::Crypto("truncated DER length".to_string()));
+        }
+        let len = data[1..1 + n]
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        Ok((len, &data[1 + n..]))
+    }
+}
+
+fn read_tlv(data: &[u8]) -> Result<(Tlv<'_>, &[u8])> {
+    let &tag = data
+        .first()
+        .ok_or_else(|| Error::Crypto("truncated DER value".to_string()))?;
+    let (len, rest) = read_length(&data[1..])?;
+    if rest.len() < len {
+        return Err(Error::Crypto("truncated DER value".to_string()));
+    }
+    let (content, remainder) = rest.split_at(len);
+    Ok((Tlv { tag, content }, remainder))
+}
+
+fn decode_integer_u64(content: &[u8]) -> Result<u64> {
+    if content.len() > 9 {
+        return Err(Error::Crypto("DER INTEGER too large".to_string()));
+    }
+    Ok(content.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+}
+
+/// Split a certificate's DER bytes into its raw `tbsCertificate` encoding
+/// (the exact bytes the signature was computed over) and the 64-byte
+/// Ed25519 signature.
+fn split_certificate(der: &[u8]) -> Result<(&[u8], [u8; 64])> {
+    let (outer, trailing) = read_tlv(der)?;
+    if outer.tag != 0x30 || !trailing.is_empty() {
+        return Err(Error::Crypto("not a DER SEQUENCE certificate".to_string()));
+    }
+
+    let body = outer.content;
+    let (_tbs_tlv, after_tbs) = read_tlv(body)?;
+    let tbs_bytes = &body[..body.len() - after_tbs.len()];
+
+    let (_alg_tlv, after_alg) = read_tlv(after_tbs)?;
+    let (sig_tlv, _) = read_tlv(after_alg)?;
+    if sig_tlv.tag != 0x03 || sig_tlv.content.is_empty() {
+        return Err(Error::Crypto("malformed certificate signature".to_string()));
+    }
+    let signature: [u8; 64] = sig_tlv.content[1..]
+        .try_into()
+        .map_err(|_| Error::Crypto("signature must be 64 bytes".to_string()))?;
+
+    Ok((tbs_bytes, signature))
+}
+
+/// Parse a `Name` (`RDNSequence`) holding a single `commonName` RDN, as
+/// produced by [`name_with_cn`].
+fn parse_cn(name_der: &[u8]) -> Result<String> {
+    let (outer, _) = read_tlv(name_der)?;
+    let (rdn, _) = read_tlv(outer.content)?; // SET
+    let (atv, _) = read_tlv(rdn.content)?; // SEQUENCE { OID, value }
+    let (_oid, after_oid) = read_tlv(atv.content)?;
+    let (value, _) = read_tlv(after_oid)?;
+    String::from_utf8(value.content.to_vec())
+        .map_err(|_| Error::Crypto("commonName is not valid UTF-8".to_string()))
+}
+
+/// Parse the `SubjectPublicKeyInfo` produced by [`subject_public_key_info`]
+/// and return the raw public key bytes.
+fn parse_spki(spki_der: &[u8]) -> Result<Vec<u8>> {
+    let (outer, _) = read_tlv(spki_der)?;
+    let (_alg, after_alg) = read_tlv(outer.content)?;
+    let (bit_string, _) = read_tlv(after_alg)?;
+    if bit_string.tag != 0x03 || bit_string.content.is_empty() {
+        return Err(Error::Crypto("malformed SubjectPublicKeyInfo".to_string()));
+    }
+    Ok(bit_string.content[1..].to_vec())
+}
+
+/// Walk the `[3] EXPLICIT Extensions` produced by [`extensions`] and extract
+/// the SAGE attestation fields.
+fn parse_attestation(extensions_der: &[u8]) -> Result<Attestation> {
+    let (wrapper, _) = read_tlv(extensions_der)?; // [3] EXPLICIT
+    let (seq, _) = read_tlv(wrapper.content)?; // SEQUENCE OF Extension
+    let want_oid = der_oid(SAGE_ATTESTATION_OID);
+
+    let mut remaining = seq.content;
+    while !remaining.is_empty() {
+        let (ext, rest) = read_tlv(remaining)?;
+        remaining = rest;
+
+        let (oid_tlv, after_oid) = read_tlv(ext.content)?;
+        let oid_encoded = der_tlv(oid_tlv.tag, oid_tlv.content);
+        if oid_encoded != want_oid {
+            continue;
+        }
+
+        let (ext_value, _) = read_tlv(after_oid)?; // OCTET STRING wrapping the attestation SEQUENCE
+        let (inner, _) = read_tlv(ext_value.content)?; // SEQUENCE { owner, nonce, registered_at }
+        let (owner_tlv, after_owner) = read_tlv(inner.content)?;
+        let (nonce_tlv, after_nonce) = read_tlv(after_owner)?;
+        let (registered_at_tlv, _) = read_tlv(after_nonce)?;
+
+        let owner: [u8; 32] = owner_tlv
+            .content
+            .try_into()
+            .map_err(|_| Error::Crypto("attestation owner must be 32 bytes".to_string()))?;
+        let nonce = decode_integer_u64(nonce_tlv.content)?;
+        let registered_at = decode_integer_u64(registered_at_tlv.content)? as i64;
+
+        return Ok(Attestation {
+            owner,
+            nonce,
+            registered_at,
+        });
+    }
+
+    Err(Error::Crypto(
+        "certificate has no SAGE attestation extension".to_string(),
+    ))
+}
+
+/// Verify `cert`'s self-signature against `expected_public_key`, and confirm
+/// its subject DID and embedded attestation match what the registry reports.
+///
+/// Returns `Ok(())` only if every check passes: signature verification,
+/// subject CN equal to `expected_did`, and the attestation's
+/// owner/nonce/registration-timestamp equal to `expected_attestation`.
+pub fn verify_certificate(
+    cert: &AgentCertificate,
+    expected_public_key: &[u8],
+    expected_did: &Did,
+    expected_attestation: &Attestation,
+) -> Result<()> {
+    let (tbs, signature_bytes) = split_certificate(&cert.der)?;
+
+    let public = Ed25519PublicKey::from_bytes(expected_public_key)
+        .map_err(|e| Error::Crypto(format!("invalid public key: {}", e)))?;
+    let signature = Signature::from(signature_bytes);
+    public
+        .verify(tbs, &signature)
+        .map_err(|_| Error::SignatureVerification)?;
+
+    let (tbs_tlv, _) = read_tlv(tbs)?;
+    let mut fields = tbs_tlv.content;
+    let (_version, rest) = read_tlv(fields)?;
+    fields = rest;
+    let (_serial, rest) = read_tlv(fields)?;
+    fields = rest;
+    let (_sig_alg, rest) = read_tlv(fields)?;
+    fields = rest;
+    let (_issuer, rest) = read_tlv(fields)?;
+    fields = rest;
+    let (_validity, rest) = read_tlv(fields)?;
+    fields = rest;
+    let (subject_tlv, rest) = read_tlv(fields)?;
+    fields = rest;
+    let (spki_tlv, rest) = read_tlv(fields)?;
+    fields = rest;
+    let (ext_tlv, _) = read_tlv(fields)?;
+
+    let subject_cn = parse_cn(&der_tlv(subject_tlv.tag, subject_tlv.content))?;
+    if subject_cn != expected_did.as_str() {
+        return Err(Error::Validation(format!(
+            "certificate subject {} does not match expected DID {}",
+            subject_cn,
+            expected_did.as_str()
+        )));
+    }
+
+    let spki_public_key = parse_spki(&der_tlv(spki_tlv.tag, spki_tlv.content))?;
+    if spki_public_key != expected_public_key {
+        return Err(Error::Validation(
+            "certificate public key does not match expected public key".to_string(),
+        ));
+    }
+
+    let attestation = parse_attestation(&der_tlv(ext_tlv.tag, ext_tlv.content))?;
+    if &attestation != expected_attestation {
+        return Err(Error::Validation(
+            "certificate attestation does not match the on-chain registry".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::Crypto;
+
+    fn sample_attestation() -> Attestation {
+        Attestation {
+            owner: [7u8; 32],
+            nonce: 3,
+            registered_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn issued_certificate_verifies_against_the_signing_key() {
+        let identity = Crypto::generate_ed25519_keypair().unwrap();
+        let did = Did::from_parts("ethereum", "0xAgent");
+        let attestation = sample_attestation();
+
+        let cert = issue_certificate(&identity, &did, attestation.clone(), 365).unwrap();
+
+        verify_certificate(&cert, &identity.public_key, &did, &attestation).unwrap();
+    }
+
+    #[test]
+    fn verification_fails_with_the_wrong_public_key() {
+        let identity = Crypto::generate_ed25519_keypair().unwrap();
+        let other = Crypto::generate_ed25519_keypair().unwrap();
+        let did = Did::from_parts("ethereum", "0xAgent");
+        let attestation = sample_attestation();
+
+        let cert = issue_certificate(&identity, &did, attestation.clone(), 365).unwrap();
+
+        assert!(verify_certificate(&cert, &other.public_key, &did, &attestation).is_err());
+    }
+
+    #[test]
+    fn verification_fails_if_attestation_no_longer_matches_the_registry() {
+        let identity = Crypto::generate_ed25519_keypair().unwrap();
+        let did = Did::from_parts("ethereum", "0xAgent");
+        let attestation = sample_attestation();
+        let mut rotated = attestation.clone();
+        rotated.nonce += 1;
+
+        let cert = issue_certificate(&identity, &did, attestation, 365).unwrap();
+
+        assert!(verify_certificate(&cert, &identity.public_key, &did, &rotated).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! Per-host circuit breaker for the HTTP client
+//!
+//! Fires fast instead of waiting out a full request timeout when a host is
+//! dead or flapping. Tracks consecutive failures keyed by request authority
+//! (`host:port`); once the failure count for an authority reaches
+//! `threshold`, the breaker opens and short-circuits further requests until
+//! `cooldown` has elapsed, at which point it allows one half-open probe
+//! through.
+
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Per-authority failure bookkeeping.
+#[derive(Debug, Clone)]
+struct Breaker {
+    failures: usize,
+    last_attempt: Instant,
+}
+
+/// Tracks per-authority breaker state for an HTTP client.
+pub struct CircuitBreaker {
+    breakers: RwLock<HashMap<String, Breaker>>,
+    threshold: usize,
+    cooldown: Duration,
+}
+
+impl CircuitBreaker {
+    /// Create a breaker that opens after `threshold` consecutive failures
+    /// for an authority, and allows one probe once `cooldown` has elapsed
+    /// since the last failed attempt.
+    pub fn new(threshold: usize, cooldown: Duration) -> Self {
+        Self {
+            breakers: RwLock::new(HashMap::new()),
+            threshold,
+            cooldown,
+        }
+    }
+
+    /// Whether a request to `authority` should be attempted right now.
+    ///
+    /// Closed (`failures < threshold`) returns `true`. A tripped breaker
+    /// whose `cooldown` has elapsed since its last attempt admits one
+    /// half-open probe: that call returns `true` and restamps the attempt
+    /// time, so other callers get `false` until the probe's outcome is
+    /// recorded or another cooldown passes.
+    pub fn should_try(&self, authority: &str) -> bool {
+        let mut breakers = self.breakers.write();
+        match breakers.get_mut(authority) {
+            None => true,
+            Some(breaker) if breaker.failures < self.threshold => true,
+            Some(breaker) if breaker.last_attempt.elapsed() > self.cooldown => {
+                breaker.last_attempt = Instant::now();
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// Record a failed request to `authority`.
+    pub fn fail(&self, authority: &str) {
+        let mut breakers = self.breakers.write();
+        let breaker = breakers.entry(authority.to_string()).or_insert(Breaker {
+            failures: 0,
+            last_attempt: Instant::now(),
+        });
+        breaker.failures += 1;
+        breaker.last_attempt = Instant::now();
+    }
+
+    /// Record a successful request to `authority`, closing its breaker.
+    pub fn succeed(&self, authority: &str) {
+        self.breakers.write().remove(authority);
+    }
+}
+
+/// Extract the `host:port` authority from a URL, for use as a breaker key.
+pub fn authority_of(url: &str) -> String {
+    let without_scheme = url.split("://").nth(1).unwrap_or(url);
+    without_scheme
+        .split('/')
+        .next()
+        .unwrap_or(without_scheme)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn authority_extraction_strips_scheme_and_path() {
+        assert_eq!(authority_of("https://example.com:8443/v1/a2a:sendMessage"), "example.com:8443");
+        assert_eq!(authority_of("http://localhost:8080/debug/health"), "localhost:8080");
+    }
+
+    #[test]
+    fn closed_breaker_allows_requests() {
+        let breaker = CircuitBreaker::new(5, Duration::from_secs(30));
+        assert!(breaker.should_try("host:1"));
+    }
+
+    #[test]
+    fn opens_after_threshold_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(30));
+        breaker.fail("host:1");
+        assert!(breaker.should_try("host:1"));
+        breaker.fail("host:1");
+        assert!(!breaker.should_try("host:1"));
+    }
+
+    #[test]
+    fn half_opens_after_cooldown() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(10));
+        breaker.fail("host:1");
+        assert!(!breaker.should_try("host:1"));
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(breaker.should_try("host:1"));
+    }
+
+    #[test]
+    fn success_resets_the_breaker() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(30));
+        breaker.fail("host:1");
+        assert!(!breaker.should_try("host:1"));
+        breaker.succeed("host:1");
+        assert!(breaker.should_try("host:1"));
+    }
+}
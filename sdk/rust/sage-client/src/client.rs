@@ -1,12 +1,17 @@
 //! SAGE client API
 
+use crate::circuit_breaker::{authority_of, CircuitBreaker};
 use crate::crypto::{setup_hpke_sender, Crypto};
 use crate::did::Did;
 use crate::error::{Error, Result};
+use crate::registry::{ResolvedServerIdentity, ServerIdentitySource};
 use crate::session::{Session, SessionManager};
+use crate::signer::{KeyPairSigner, Signer};
+use crate::store::SessionStore;
 use crate::types::*;
 use reqwest::Client as HttpClient;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Client configuration
 #[derive(Debug, Clone)]
@@ -14,6 +19,29 @@ pub struct ClientConfig {
     pub base_url: String,
     pub timeout_seconds: u64,
     pub max_sessions: usize,
+    /// Consecutive failures against a single authority (host:port) before
+    /// its circuit breaker opens.
+    pub circuit_breaker_threshold: usize,
+    /// How long an open breaker stays open before allowing one half-open
+    /// probe request through.
+    pub circuit_breaker_cooldown_seconds: u64,
+    /// Maximum allowed clock skew, in seconds, between now and a server
+    /// response's signed timestamp before it's rejected as a possible
+    /// replay.
+    pub response_skew_seconds: i64,
+    /// Optional HTTP/HTTPS/SOCKS proxy URL (e.g. `socks5://127.0.0.1:1080`)
+    /// to route all SAGE traffic through.
+    pub proxy: Option<String>,
+    /// Extra root certificates (PEM or DER) to trust in addition to the
+    /// platform's default store, for servers presenting an internal CA cert.
+    pub extra_root_certs: Vec<Vec<u8>>,
+    /// Skip TLS certificate validation entirely. Test-only — never set this
+    /// in production.
+    pub accept_invalid_certs: bool,
+    /// Maximum idle HTTP connections kept open per host.
+    pub pool_max_idle_per_host: usize,
+    /// How long an idle pooled connection is kept open before being closed.
+    pub pool_idle_timeout_seconds: u64,
 }
 
 impl ClientConfig {
@@ -23,6 +51,14 @@ impl ClientConfig {
             base_url: base_url.trim_end_matches('/').to_string(),
             timeout_seconds: 30,
             max_sessions: 100,
+            circuit_breaker_threshold: 5,
+            circuit_breaker_cooldown_seconds: 30,
+            response_skew_seconds: 300,
+            proxy: None,
+            extra_root_certs: Vec::new(),
+            accept_invalid_certs: false,
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout_seconds: 90,
         }
     }
 }
@@ -31,36 +67,145 @@ impl ClientConfig {
 pub struct Client {
     config: ClientConfig,
     http_client: HttpClient,
-    identity_keypair: Option<KeyPair>,
+    identity_signer: Option<Box<dyn Signer>>,
     kem_keypair: Option<KeyPair>,
     client_did: Option<String>,
     session_manager: SessionManager,
+    circuit_breaker: CircuitBreaker,
+    identity_source: ServerIdentitySource,
 }
 
 impl Client {
-    /// Create new SAGE client
+    /// Create new SAGE client, trusting the development-only `/debug`
+    /// endpoints for server identity and signing in-process with a freshly
+    /// generated keypair. Use [`Client::with_identity_source`] to bind the
+    /// client to an on-chain registry, or [`Client::with_signer`] to keep
+    /// the identity private key out of this process entirely.
     pub async fn new(config: ClientConfig) -> Result<Self> {
-        let http_client = HttpClient::builder()
+        Self::with_identity_source(config, ServerIdentitySource::DebugEndpoints).await
+    }
+
+    /// Create a new SAGE client that resolves server identity via `identity_source`.
+    pub async fn with_identity_source(
+        config: ClientConfig,
+        identity_source: ServerIdentitySource,
+    ) -> Result<Self> {
+        Self::build(config, identity_source, None, None).await
+    }
+
+    /// Create a new SAGE client that signs outgoing requests with `signer`
+    /// instead of an in-process keypair, e.g. an [`crate::signer::UnixSocketSigner`]
+    /// backed by a hardware token or a separate hardened process.
+    pub async fn with_signer(
+        config: ClientConfig,
+        identity_source: ServerIdentitySource,
+        signer: Box<dyn Signer>,
+    ) -> Result<Self> {
+        Self::build(config, identity_source, Some(signer), None).await
+    }
+
+    /// Create a new SAGE client whose sessions persist to `session_store`
+    /// instead of the default in-memory map, so sessions survive a restart
+    /// or can be shared across client instances. See
+    /// [`Client::resume_session`] to rehydrate a specific session from it.
+    pub async fn with_session_store(
+        config: ClientConfig,
+        identity_source: ServerIdentitySource,
+        session_store: Arc<dyn SessionStore>,
+    ) -> Result<Self> {
+        Self::build(config, identity_source, None, Some(session_store)).await
+    }
+
+    async fn build(
+        config: ClientConfig,
+        identity_source: ServerIdentitySource,
+        identity_signer: Option<Box<dyn Signer>>,
+        session_store: Option<Arc<dyn SessionStore>>,
+    ) -> Result<Self> {
+        let mut http_builder = HttpClient::builder()
             .timeout(std::time::Duration::from_secs(config.timeout_seconds))
-            .build()
-            .map_err(|e| Error::Network(e))?;
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_seconds))
+            .danger_accept_invalid_certs(config.accept_invalid_certs);
+
+        if let Some(proxy_url) = &config.proxy {
+            let proxy = reqwest::Proxy::all(proxy_url).map_err(|e| Error::Network(e))?;
+            http_builder = http_builder.proxy(proxy);
+        }
+
+        for cert_bytes in &config.extra_root_certs {
+            let cert = reqwest::Certificate::from_pem(cert_bytes)
+                .or_else(|_| reqwest::Certificate::from_der(cert_bytes))
+                .map_err(|e| Error::Network(e))?;
+            http_builder = http_builder.add_root_certificate(cert);
+        }
+
+        let http_client = http_builder.build().map_err(|e| Error::Network(e))?;
+
+        let circuit_breaker = CircuitBreaker::new(
+            config.circuit_breaker_threshold,
+            Duration::from_secs(config.circuit_breaker_cooldown_seconds),
+        );
+
+        let session_manager = match session_store {
+            Some(store) => SessionManager::with_store(config.max_sessions, store),
+            None => SessionManager::new(config.max_sessions),
+        };
 
         let mut client = Self {
             config: config.clone(),
             http_client,
-            identity_keypair: None,
+            identity_signer,
             kem_keypair: None,
             client_did: None,
-            session_manager: SessionManager::new(config.max_sessions),
+            session_manager,
+            circuit_breaker,
+            identity_source,
         };
 
         client.initialize().await?;
         Ok(client)
     }
 
-    /// Initialize client with keypairs
+    /// Run `request`, short-circuiting with [`Error::CircuitOpen`] if `url`'s
+    /// authority (host:port) has a tripped breaker, and recording the
+    /// outcome against that authority otherwise.
+    async fn send_guarded<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T> {
+        let authority = authority_of(url);
+        if !self.circuit_breaker.should_try(&authority) {
+            return Err(Error::CircuitOpen(authority));
+        }
+
+        match request.send().await.and_then(|r| r.error_for_status()) {
+            Ok(response) => match response.json::<T>().await {
+                Ok(parsed) => {
+                    self.circuit_breaker.succeed(&authority);
+                    Ok(parsed)
+                }
+                Err(e) => {
+                    self.circuit_breaker.fail(&authority);
+                    Err(Error::Network(e))
+                }
+            },
+            Err(e) => {
+                self.circuit_breaker.fail(&authority);
+                Err(Error::Network(e))
+            }
+        }
+    }
+
+    /// Initialize client keypairs, generating an in-process identity signer
+    /// if one wasn't supplied via [`Client::with_signer`].
     pub async fn initialize(&mut self) -> Result<()> {
-        self.identity_keypair = Some(Crypto::generate_ed25519_keypair()?);
+        if self.identity_signer.is_none() {
+            self.identity_signer = Some(Box::new(KeyPairSigner::new(
+                Crypto::generate_ed25519_keypair()?,
+            )));
+        }
         self.kem_keypair = Some(Crypto::generate_x25519_keypair()?);
         Ok(())
     }
@@ -68,13 +213,8 @@ impl Client {
     /// Get server's KEM public key
     pub async fn get_server_kem_key(&self) -> Result<Vec<u8>> {
         let url = format!("{}/debug/kem-pub", self.config.base_url);
-        let response = self
-            .http_client
-            .get(&url)
-            .send()
-            .await?
-            .json::<KemPublicKeyResponse>()
-            .await?;
+        let response: KemPublicKeyResponse =
+            self.send_guarded(&url, self.http_client.get(&url)).await?;
 
         Crypto::base64_decode(&response.kem_public_key)
     }
@@ -82,35 +222,77 @@ impl Client {
     /// Get server's DID
     pub async fn get_server_did(&self) -> Result<String> {
         let url = format!("{}/debug/server-did", self.config.base_url);
-        let response = self
-            .http_client
-            .get(&url)
-            .send()
-            .await?
-            .json::<ServerDidResponse>()
-            .await?;
+        let response: ServerDidResponse =
+            self.send_guarded(&url, self.http_client.get(&url)).await?;
 
         Ok(response.did)
     }
 
+    /// Get server's Ed25519 signing public key
+    pub async fn get_server_signing_key(&self) -> Result<Vec<u8>> {
+        let url = format!("{}/debug/server-pub", self.config.base_url);
+        let response: ServerPublicKeyResponse =
+            self.send_guarded(&url, self.http_client.get(&url)).await?;
+
+        Crypto::base64_decode(&response.public_key)
+    }
+
+    /// Resolve `server_did`'s signing and KEM public keys per `identity_source`.
+    ///
+    /// Under [`ServerIdentitySource::DebugEndpoints`] this trusts whatever
+    /// the HTTP peer hands back from `/debug/kem-pub` and `/debug/server-pub`;
+    /// under [`ServerIdentitySource::OnChain`] it's read from the registry
+    /// contract instead, so a compromised or spoofed HTTP peer can't hand
+    /// the client the wrong key material.
+    async fn resolve_server_identity(&self, server_did: &str) -> Result<ResolvedServerIdentity> {
+        match &self.identity_source {
+            ServerIdentitySource::DebugEndpoints => Ok(ResolvedServerIdentity {
+                public_key: self.get_server_signing_key().await?,
+                public_kem_key: self.get_server_kem_key().await?,
+            }),
+            ServerIdentitySource::OnChain(registry) => registry.resolve(server_did).await,
+        }
+    }
+
+    /// Verify a server response's signature over
+    /// `server_did|client_did|response_b64|timestamp`, rejecting both bad
+    /// signatures and timestamps outside `response_skew_seconds` of now as
+    /// [`Error::InvalidServerSignature`].
+    fn verify_server_response(
+        &self,
+        server_did: &str,
+        client_did: &str,
+        response_b64: &str,
+        timestamp: i64,
+        signature_b64: &str,
+        server_public_key: &[u8],
+    ) -> Result<()> {
+        if (current_timestamp() - timestamp).abs() > self.config.response_skew_seconds {
+            return Err(Error::InvalidServerSignature);
+        }
+
+        let signature = Crypto::base64_decode(signature_b64)?;
+        let to_verify = format!(
+            "{}|{}|{}|{}",
+            server_did, client_did, response_b64, timestamp
+        );
+
+        match Crypto::verify(to_verify.as_bytes(), &signature, server_public_key) {
+            Ok(true) => Ok(()),
+            _ => Err(Error::InvalidServerSignature),
+        }
+    }
+
     /// Health check
     pub async fn health_check(&self) -> Result<HealthStatus> {
         let url = format!("{}/debug/health", self.config.base_url);
-        let response = self
-            .http_client
-            .get(&url)
-            .send()
-            .await?
-            .json::<HealthStatus>()
-            .await?;
-
-        Ok(response)
+        self.send_guarded(&url, self.http_client.get(&url)).await
     }
 
     /// Register agent (development only)
     pub async fn register_agent(&mut self, did: &str, name: &str) -> Result<()> {
         let identity = self
-            .identity_keypair
+            .identity_signer
             .as_ref()
             .ok_or(Error::NotInitialized)?;
         let kem = self.kem_keypair.as_ref().ok_or(Error::NotInitialized)?;
@@ -119,17 +301,13 @@ impl Client {
             did: did.to_string(),
             name: name.to_string(),
             is_active: true,
-            public_key: Crypto::base64_encode(&identity.public_key),
+            public_key: Crypto::base64_encode(identity.public_key()),
             public_kem_key: Crypto::base64_encode(&kem.public_key),
         };
 
         let url = format!("{}/debug/register-agent", self.config.base_url);
-        self.http_client
-            .post(&url)
-            .json(&metadata)
-            .send()
-            .await?
-            .json::<RegisterResponse>()
+        let _: RegisterResponse = self
+            .send_guarded(&url, self.http_client.post(&url).json(&metadata))
             .await?;
 
         self.client_did = Some(did.to_string());
@@ -144,12 +322,12 @@ impl Client {
             .ok_or(Error::NotInitialized)?
             .clone();
         let identity = self
-            .identity_keypair
+            .identity_signer
             .as_ref()
             .ok_or(Error::NotInitialized)?;
 
-        let server_kem_key = self.get_server_kem_key().await?;
-        let (mut hpke_ctx, encapsulated_key) = setup_hpke_sender(&server_kem_key)?;
+        let server_identity = self.resolve_server_identity(server_did).await?;
+        let (mut hpke_ctx, encapsulated_key) = setup_hpke_sender(&server_identity.public_kem_key)?;
 
         let handshake_data = serde_json::json!({
             "type": "handshake",
@@ -165,7 +343,7 @@ impl Client {
 
         let timestamp = current_timestamp();
         let to_sign = format!("{}|{}|{}|{}", client_did, server_did, message_b64, timestamp);
-        let signature = Crypto::sign(to_sign.as_bytes(), &identity.private_key)?;
+        let signature = identity.sign(to_sign.as_bytes()).await?;
         let signature_b64 = Crypto::base64_encode(&signature);
 
         let request = HandshakeRequest {
@@ -177,15 +355,19 @@ impl Client {
         };
 
         let url = format!("{}/v1/a2a:sendMessage", self.config.base_url);
-        let response = self
-            .http_client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await?
-            .json::<HandshakeResponse>()
+        let response: HandshakeResponse = self
+            .send_guarded(&url, self.http_client.post(&url).json(&request))
             .await?;
 
+        self.verify_server_response(
+            server_did,
+            &client_did,
+            &response.response,
+            response.timestamp,
+            &response.signature,
+            &server_identity.public_key,
+        )?;
+
         let session_id = response.session_id.clone();
         let session = Session::new(
             session_id.clone(),
@@ -210,14 +392,14 @@ impl Client {
         let message_b64 = Crypto::base64_encode(&ciphertext);
 
         let client_did = self.client_did.as_ref().ok_or(Error::NotInitialized)?;
-        let identity = self.identity_keypair.as_ref().ok_or(Error::NotInitialized)?;
+        let identity = self.identity_signer.as_ref().ok_or(Error::NotInitialized)?;
 
         let timestamp = current_timestamp();
         let to_sign = format!(
             "{}|{}|{}|{}",
             client_did, session.server_did, message_b64, timestamp
         );
-        let signature = Crypto::sign(to_sign.as_bytes(), &identity.private_key)?;
+        let signature = identity.sign(to_sign.as_bytes()).await?;
         let signature_b64 = Crypto::base64_encode(&signature);
 
         let request = MessageRequest {
@@ -229,28 +411,63 @@ impl Client {
         };
 
         let url = format!("{}/v1/a2a:sendMessage", self.config.base_url);
-        let response = self
-            .http_client
-            .post(&url)
-            .json(&request)
-            .header("X-Session-ID", session_id)
-            .send()
-            .await?
-            .json::<MessageResponse>()
+        let response: MessageResponse = self
+            .send_guarded(
+                &url,
+                self.http_client
+                    .post(&url)
+                    .json(&request)
+                    .header("X-Session-ID", session_id),
+            )
             .await?;
 
+        let server_identity = self.resolve_server_identity(&session.server_did).await?;
+        self.verify_server_response(
+            &session.server_did,
+            client_did,
+            &response.response,
+            response.timestamp,
+            &response.signature,
+            &server_identity.public_key,
+        )?;
+
         let response_bytes = Crypto::base64_decode(&response.response)?;
-        let session = self
-            .session_manager
-            .get_session(session_id)
-            .ok_or_else(|| Error::Session("Session not found".to_string()))?;
         session.decrypt(&response_bytes)
     }
 
     /// Get active session count
-    pub fn active_sessions(&mut self) -> usize {
+    pub fn active_sessions(&self) -> usize {
         self.session_manager.count()
     }
+
+    /// Rehydrate `session_id` from the configured session store if it isn't
+    /// already held in memory, e.g. after this process restarted or to pick
+    /// up a session a peer instance already established against the same
+    /// shared store. Returns whether a live session is resident afterward.
+    pub fn resume_session(&self, session_id: &str) -> Result<bool> {
+        self.session_manager.resume(session_id)
+    }
+
+    /// Open a streaming subscription over `session_id`, yielding a handle for
+    /// correlated request/response calls and a `Stream` of decrypted,
+    /// server-pushed events, all sharing one long-lived connection.
+    pub async fn subscribe(
+        &self,
+        session_id: &str,
+    ) -> Result<(crate::stream::StreamHandle, crate::stream::EventStream)> {
+        let session = self
+            .session_manager
+            .get_session(session_id)
+            .ok_or_else(|| Error::Session("Session not found".to_string()))?;
+
+        let ws_url = self
+            .config
+            .base_url
+            .replacen("http", "ws", 1)
+            + "/v1/a2a:stream";
+
+        crate::stream::StreamHandle::connect(&ws_url, session).await
+    }
 }
 
 fn current_timestamp() -> i64 {
@@ -1,7 +1,7 @@
 //! Cryptography module for SAGE client
 
 use crate::error::{Error, Result};
-use crate::types::{KeyPair, KeyType};
+use crate::types::{KeyPair, KeyType, SecretBytes};
 use aes_gcm::{
     aead::{Aead, KeyInit},
     Aes256Gcm, Nonce,
@@ -12,6 +12,17 @@ use hkdf::Hkdf;
 use rand::rngs::OsRng;
 use sha2::{Digest, Sha256};
 use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+use zeroize::Zeroize;
+
+fn require_equal_batch_lengths(messages: usize, signatures: usize, public_keys: usize) -> Result<()> {
+    if messages != signatures || messages != public_keys {
+        return Err(Error::Crypto(format!(
+            "batch length mismatch: {} messages, {} signatures, {} public keys",
+            messages, signatures, public_keys
+        )));
+    }
+    Ok(())
+}
 
 /// Cryptographic operations
 pub struct Crypto;
@@ -22,7 +33,7 @@ impl Crypto {
         let keypair = Keypair::generate(&mut OsRng);
 
         Ok(KeyPair {
-            private_key: keypair.secret.to_bytes().to_vec(),
+            private_key: SecretBytes::new(keypair.secret.to_bytes().to_vec()),
             public_key: keypair.public.to_bytes().to_vec(),
             key_type: KeyType::Ed25519,
         })
@@ -34,7 +45,7 @@ impl Crypto {
         let public = PublicKey::from(&secret);
 
         Ok(KeyPair {
-            private_key: secret.to_bytes().to_vec(),
+            private_key: SecretBytes::new(secret.to_bytes().to_vec()),
             public_key: public.to_bytes().to_vec(),
             key_type: KeyType::X25519,
         })
@@ -42,13 +53,14 @@ impl Crypto {
 
     /// Sign message with Ed25519 private key
     pub fn sign(message: &[u8], private_key: &[u8]) -> Result<Vec<u8>> {
-        let key_bytes: [u8; 32] = private_key
+        let mut key_bytes: [u8; 32] = private_key
             .try_into()
             .map_err(|_| Error::Crypto("Invalid private key length".to_string()))?;
 
         use ed25519_dalek::{SecretKey, ExpandedSecretKey};
         let secret = SecretKey::from_bytes(&key_bytes)
             .map_err(|e| Error::Crypto(format!("Invalid secret key: {}", e)))?;
+        key_bytes.zeroize();
         let expanded = ExpandedSecretKey::from(&secret);
         let public = Ed25519PublicKey::from(&secret);
         let signature = expanded.sign(message, &public);
@@ -77,9 +89,65 @@ impl Crypto {
             .map_err(|_| Error::SignatureVerification)
     }
 
+    /// Verify many Ed25519 `(message, signature, public_key)` triples at once.
+    ///
+    /// Uses ed25519-dalek's batch API, which folds all verification
+    /// equations into a single random-linear-combination check (each
+    /// equation scaled by a random 128-bit scalar) so N signatures cost far
+    /// less than N individual checks. Useful for an agent validating a
+    /// stream of inbound `Message`s, or a client pre-validating several key
+    /// rotations before submitting them.
+    ///
+    /// Batch verification only yields a single pass/fail for the whole set,
+    /// so on failure this falls back to verifying each triple individually
+    /// and returns the indices that actually failed (empty on success).
+    pub fn verify_batch(
+        messages: &[&[u8]],
+        signatures: &[Vec<u8>],
+        public_keys: &[Vec<u8>],
+    ) -> Result<Vec<usize>> {
+        require_equal_batch_lengths(messages.len(), signatures.len(), public_keys.len())?;
+
+        let parsed_signatures: Vec<Signature> = signatures
+            .iter()
+            .map(|s| {
+                let bytes: [u8; 64] = s
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| Error::Crypto("Invalid signature length".to_string()))?;
+                Ok(Signature::from(bytes))
+            })
+            .collect::<Result<_>>()?;
+
+        let parsed_keys: Vec<Ed25519PublicKey> = public_keys
+            .iter()
+            .map(|k| {
+                let bytes: [u8; 32] = k
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| Error::Crypto("Invalid public key length".to_string()))?;
+                Ed25519PublicKey::from_bytes(&bytes)
+                    .map_err(|e| Error::Crypto(format!("Invalid public key: {}", e)))
+            })
+            .collect::<Result<_>>()?;
+
+        match ed25519_dalek::verify_batch(messages, &parsed_signatures, &parsed_keys) {
+            Ok(()) => Ok(Vec::new()),
+            Err(_) => {
+                let mut failed = Vec::new();
+                for i in 0..messages.len() {
+                    if Crypto::verify(messages[i], &signatures[i], &public_keys[i]).is_err() {
+                        failed.push(i);
+                    }
+                }
+                Ok(failed)
+            }
+        }
+    }
+
     /// Compute X25519 Diffie-Hellman shared secret
     pub fn compute_dh(private_key: &[u8], public_key: &[u8]) -> Result<Vec<u8>> {
-        let secret_bytes: [u8; 32] = private_key
+        let mut secret_bytes: [u8; 32] = private_key
             .try_into()
             .map_err(|_| Error::Crypto("Invalid private key length".to_string()))?;
 
@@ -88,6 +156,7 @@ impl Crypto {
             .map_err(|_| Error::Crypto("Invalid public key length".to_string()))?;
 
         let secret = StaticSecret::from(secret_bytes);
+        secret_bytes.zeroize();
         let public = PublicKey::from(public_bytes);
 
         let shared_secret = secret.diffie_hellman(&public);
@@ -107,11 +176,12 @@ impl Crypto {
 
     /// Encrypt with AES-256-GCM
     pub fn encrypt_aes_gcm(plaintext: &[u8], key: &[u8], nonce_bytes: &[u8]) -> Result<Vec<u8>> {
-        let key_array: [u8; 32] = key
+        let mut key_array: [u8; 32] = key
             .try_into()
             .map_err(|_| Error::Crypto("Invalid key length".to_string()))?;
 
         let cipher = Aes256Gcm::new(&key_array.into());
+        key_array.zeroize();
 
         let nonce_array: [u8; 12] = nonce_bytes
             .try_into()
@@ -128,11 +198,12 @@ impl Crypto {
 
     /// Decrypt with AES-256-GCM
     pub fn decrypt_aes_gcm(ciphertext: &[u8], key: &[u8], nonce_bytes: &[u8]) -> Result<Vec<u8>> {
-        let key_array: [u8; 32] = key
+        let mut key_array: [u8; 32] = key
             .try_into()
             .map_err(|_| Error::Crypto("Invalid key length".to_string()))?;
 
         let cipher = Aes256Gcm::new(&key_array.into());
+        key_array.zeroize();
 
         let nonce_array: [u8; 12] = nonce_bytes
             .try_into()
@@ -168,6 +239,9 @@ impl Crypto {
 }
 
 /// HPKE context for encryption/decryption
+///
+/// Wipes its symmetric key from memory on drop via `zeroize`.
+#[derive(zeroize::Zeroize, zeroize::ZeroizeOnDrop)]
 pub struct HpkeContext {
     key: Vec<u8>,
     sequence: u64,
@@ -209,6 +283,17 @@ impl HpkeContext {
 
         Crypto::decrypt_aes_gcm(actual_ciphertext, &self.key, nonce)
     }
+
+    /// Export the raw keying material and current sequence number so the
+    /// context can be persisted and later restored with [`HpkeContext::restore`].
+    pub fn export(&self) -> (Vec<u8>, u64) {
+        (self.key.clone(), self.sequence)
+    }
+
+    /// Rebuild a context from previously exported keying material.
+    pub fn restore(key: Vec<u8>, sequence: u64) -> Self {
+        Self { key, sequence }
+    }
 }
 
 /// Setup HPKE as sender (encapsulation)
@@ -241,7 +326,7 @@ pub fn setup_hpke_receiver(
     encapsulated_key: &[u8],
     receiver_private_key: &[u8],
 ) -> Result<HpkeContext> {
-    let private_bytes: [u8; 32] = receiver_private_key
+    let mut private_bytes: [u8; 32] = receiver_private_key
         .try_into()
         .map_err(|_| Error::Crypto("Invalid receiver private key length".to_string()))?;
 
@@ -250,6 +335,7 @@ pub fn setup_hpke_receiver(
         .map_err(|_| Error::Crypto("Invalid encapsulated key length".to_string()))?;
 
     let secret = StaticSecret::from(private_bytes);
+    private_bytes.zeroize();
     let public = PublicKey::from(public_bytes);
 
     // Compute shared secret
@@ -294,6 +380,39 @@ mod tests {
         assert!(is_valid);
     }
 
+    #[test]
+    fn test_verify_batch_all_valid() {
+        let keypairs: Vec<_> = (0..4).map(|_| Crypto::generate_ed25519_keypair().unwrap()).collect();
+        let messages: Vec<&[u8]> = vec![b"one", b"two", b"three", b"four"];
+        let signatures: Vec<Vec<u8>> = messages
+            .iter()
+            .zip(&keypairs)
+            .map(|(m, kp)| Crypto::sign(m, &kp.private_key).unwrap())
+            .collect();
+        let public_keys: Vec<Vec<u8>> = keypairs.iter().map(|kp| kp.public_key.clone()).collect();
+
+        let failed = Crypto::verify_batch(&messages, &signatures, &public_keys).unwrap();
+        assert!(failed.is_empty());
+    }
+
+    #[test]
+    fn test_verify_batch_reports_failing_index() {
+        let keypairs: Vec<_> = (0..3).map(|_| Crypto::generate_ed25519_keypair().unwrap()).collect();
+        let messages: Vec<&[u8]> = vec![b"one", b"two", b"three"];
+        let mut signatures: Vec<Vec<u8>> = messages
+            .iter()
+            .zip(&keypairs)
+            .map(|(m, kp)| Crypto::sign(m, &kp.private_key).unwrap())
+            .collect();
+        let public_keys: Vec<Vec<u8>> = keypairs.iter().map(|kp| kp.public_key.clone()).collect();
+
+        // Corrupt the signature at index 1.
+        signatures[1][0] ^= 0xFF;
+
+        let failed = Crypto::verify_batch(&messages, &signatures, &public_keys).unwrap();
+        assert_eq!(failed, vec![1]);
+    }
+
     #[test]
     fn test_hpke_encryption() {
         let receiver_keypair = Crypto::generate_x25519_keypair().unwrap();
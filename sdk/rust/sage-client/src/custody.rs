@@ -0,0 +1,335 @@
+//! Distributed key custody via Shamir secret sharing
+//!
+//! For high-value agents, no single operator should hold the full Ed25519
+//! signing key. This module splits a 32-byte seed into `n` shares with a
+//! `t`-of-`n` reconstruction threshold, so the secret only exists whole
+//! transiently at signing time.
+//!
+//! Sharing is done byte-wise over GF(256) (32 independent degree-`t-1`
+//! polynomials, one per seed byte), rather than as a single polynomial over
+//! the Ed25519 scalar field: an Ed25519 seed is an arbitrary 32-byte value,
+//! not a scalar reduced mod the group order `l` (~2^252.5), so reducing it
+//! mod `l` before sharing would make the round-trip lossy for the ~91% of
+//! seeds that aren't already less than `l`. GF(256) sharing has no such
+//! restriction — every byte value is a valid field element, so any 32-byte
+//! secret round-trips exactly.
+//!
+//! This drops the Feldman public-commitment verification the scalar-field
+//! version had: Feldman's binding commitment (`coeff * basepoint`) is only
+//! hiding because the coefficient lives in a field large enough that
+//! brute-forcing it back out of the commitment is infeasible. A GF(256)
+//! coefficient has only 256 possible values, so committing it the same way
+//! would let anyone recover the coefficient (and, for the constant term, the
+//! secret share itself) by trying all 256 candidates.
+//!
+//! In its place, [`split_key`] publishes a SHA-256 commitment to each share's
+//! *value* (not its coefficients), which [`verify_share`] lets a holder check
+//! their share against. This doesn't give Feldman's property of catching a
+//! dealer that hands out inconsistent shares in the first place (detecting
+//! that still needs an out-of-band trusted setup), but it does let a holder
+//! detect a share that was corrupted or substituted after the dealer
+//! committed to it -- in transit, at rest, or by a custodian.
+
+use crate::crypto::Crypto;
+use crate::did::{Did, DidDocument};
+use crate::error::{Error, Result};
+use crate::types::KeyPair;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// A single `(x, y)` share of a split secret. `index` is the non-zero
+/// x-coordinate shared by all 32 byte-polynomials; `value` is `f(index)`
+/// for each of those 32 polynomials, in byte order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Share {
+    pub index: u8,
+    pub value: [u8; 32],
+}
+
+/// Output of [`split_key`]: the shares to distribute, plus a public
+/// commitment to each one that [`verify_share`] checks against.
+#[derive(Debug, Clone)]
+pub struct SharingResult {
+    pub shares: Vec<Share>,
+    /// `commitments[i]` is [`commit_share`] of `shares[i]`. Safe to publish
+    /// alongside the secret's public key -- unlike the shares themselves,
+    /// these reveal nothing about the secret (SHA-256 is one-way).
+    pub commitments: Vec<[u8; 32]>,
+}
+
+/// `SHA-256(index || value)`, binding a commitment to both the share's
+/// x-coordinate and its value so a holder can't satisfy another holder's
+/// commitment.
+fn commit_share(share: &Share) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([share.index]);
+    hasher.update(share.value);
+    hasher.finalize().into()
+}
+
+/// Verify that `share` matches one of the commitments [`split_key`]
+/// published, detecting a share that was corrupted or substituted after the
+/// dealer committed to it.
+pub fn verify_share(share: &Share, commitments: &[[u8; 32]]) -> Result<()> {
+    if commitments.contains(&commit_share(share)) {
+        Ok(())
+    } else {
+        Err(Error::Validation(format!(
+            "share {} does not match any published commitment",
+            share.index
+        )))
+    }
+}
+
+/// Multiply two GF(256) elements under the AES/Rijndael reduction
+/// polynomial `x^8 + x^4 + x^3 + x + 1` (0x11B).
+fn gf256_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let high_bit_set = a & 0x80 != 0;
+        a <<= 1;
+        if high_bit_set {
+            a ^= 0x1B;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// `a^-1` in GF(256), via `a^254 = a^(255-1)` (Fermat's little theorem
+/// analogue: every nonzero element has multiplicative order dividing 255).
+fn gf256_inv(a: u8) -> u8 {
+    debug_assert_ne!(a, 0, "zero has no multiplicative inverse");
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = gf256_mul(result, base);
+        }
+        base = gf256_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn gf256_div(a: u8, b: u8) -> u8 {
+    gf256_mul(a, gf256_inv(b))
+}
+
+/// Evaluate the polynomial with the given coefficients (lowest degree first)
+/// at `x`, via Horner's method over GF(256).
+fn eval_poly_gf256(coeffs: &[u8], x: u8) -> u8 {
+    let mut acc = 0u8;
+    for &c in coeffs.iter().rev() {
+        acc = gf256_mul(acc, x) ^ c;
+    }
+    acc
+}
+
+/// Split `secret` (an Ed25519 seed) into `n` shares requiring any `t` of them
+/// to reconstruct.
+///
+/// For each of the 32 seed bytes, picks an independent random degree-`t-1`
+/// polynomial over GF(256) whose constant term is that byte, and evaluates
+/// it at `x = 1..=n`.
+pub fn split_key(secret: &[u8; 32], t: usize, n: usize) -> Result<SharingResult> {
+    if t == 0 || n == 0 || t > n {
+        return Err(Error::Validation(format!(
+            "invalid threshold: need 1 <= t <= n, got t={}, n={}",
+            t, n
+        )));
+    }
+    if n > 255 {
+        return Err(Error::Validation("n must fit in a non-zero u8 x-coordinate (<= 255)".to_string()));
+    }
+
+    // coeffs[k][byte_pos] is the x^k coefficient of byte_pos's polynomial.
+    let mut coeffs: Vec<[u8; 32]> = Vec::with_capacity(t);
+    coeffs.push(*secret);
+    for _ in 1..t {
+        let mut c = [0u8; 32];
+        OsRng.fill_bytes(&mut c);
+        coeffs.push(c);
+    }
+
+    let shares: Vec<Share> = (1..=n as u16)
+        .map(|i| {
+            let x = i as u8;
+            let mut value = [0u8; 32];
+            for (byte_pos, slot) in value.iter_mut().enumerate() {
+                let poly: Vec<u8> = coeffs.iter().map(|c| c[byte_pos]).collect();
+                *slot = eval_poly_gf256(&poly, x);
+            }
+            Share { index: x, value }
+        })
+        .collect();
+    let commitments = shares.iter().map(commit_share).collect();
+
+    Ok(SharingResult { shares, commitments })
+}
+
+/// Reconstruct the original 32-byte seed from any `t` of the shares produced
+/// by [`split_key`], via Lagrange interpolation of each byte-polynomial at
+/// `x = 0` over GF(256).
+pub fn reconstruct(shares: &[Share]) -> Result<[u8; 32]> {
+    if shares.is_empty() {
+        return Err(Error::Validation("need at least one share to reconstruct".to_string()));
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    for share in shares {
+        if !seen.insert(share.index) {
+            return Err(Error::Validation(format!("duplicate share index {}", share.index)));
+        }
+    }
+
+    let mut secret = [0u8; 32];
+    for (byte_pos, slot) in secret.iter_mut().enumerate() {
+        let mut acc = 0u8;
+        for (i, share_i) in shares.iter().enumerate() {
+            let xi = share_i.index;
+            let yi = share_i.value[byte_pos];
+
+            let mut numerator = 1u8;
+            let mut denominator = 1u8;
+            for (j, share_j) in shares.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                let xj = share_j.index;
+                numerator = gf256_mul(numerator, xj);
+                denominator = gf256_mul(denominator, xi ^ xj);
+            }
+
+            acc ^= gf256_mul(yi, gf256_div(numerator, denominator));
+        }
+        *slot = acc;
+    }
+
+    Ok(secret)
+}
+
+/// Generate a fresh Ed25519 identity (and X25519 KEM key) for an agent, split
+/// the signing seed into `t`-of-`n` custody shares, and return a
+/// `DidDocument` carrying only the public keys. The secret is never
+/// persisted whole: distribute the returned shares to separate custodians
+/// and call [`reconstruct`] only transiently, at signing time.
+pub fn split_for_registration(network: &str, t: usize, n: usize) -> Result<(DidDocument, SharingResult)> {
+    let identity: KeyPair = Crypto::generate_ed25519_keypair()?;
+    let kem = Crypto::generate_x25519_keypair()?;
+
+    let seed: [u8; 32] = identity
+        .private_key
+        .as_bytes()
+        .try_into()
+        .map_err(|_| Error::Crypto("Ed25519 seed must be 32 bytes".to_string()))?;
+    let sharing = split_key(&seed, t, n)?;
+
+    let address = crate::brain_key::derive_address(&identity.public_key);
+    let did = Did::from_parts(network, &address);
+
+    let document = DidDocument {
+        did,
+        public_key: identity.public_key,
+        public_kem_key: kem.public_key,
+        owner_address: address,
+        is_active: true,
+        revoked: false,
+    };
+
+    Ok((document, sharing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_secret() -> [u8; 32] {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        seed
+    }
+
+    #[test]
+    fn reconstructs_from_any_t_shares() {
+        let secret = sample_secret();
+        let sharing = split_key(&secret, 3, 5).unwrap();
+
+        let subset_a = vec![sharing.shares[0].clone(), sharing.shares[2].clone(), sharing.shares[4].clone()];
+        let subset_b = vec![sharing.shares[1].clone(), sharing.shares[2].clone(), sharing.shares[3].clone()];
+
+        assert_eq!(reconstruct(&subset_a).unwrap(), secret);
+        assert_eq!(reconstruct(&subset_b).unwrap(), secret);
+    }
+
+    #[test]
+    fn reconstructs_arbitrary_seeds_not_just_reduced_scalars() {
+        // A seed >= the Ed25519 group order l (~2^252.5): read little-endian,
+        // the bits above 2^252 that are set in the top byte 0x7F guarantee this. The old scalar-field sharing silently reduced
+        // this mod l before splitting, corrupting it.
+        let mut secret = [0xFFu8; 32];
+        secret[31] = 0x7F;
+        let sharing = split_key(&secret, 3, 5).unwrap();
+        let subset = vec![sharing.shares[0].clone(), sharing.shares[1].clone(), sharing.shares[2].clone()];
+        assert_eq!(reconstruct(&subset).unwrap(), secret);
+    }
+
+    #[test]
+    fn fails_to_reconstruct_with_too_few_shares() {
+        let secret = sample_secret();
+        let sharing = split_key(&secret, 3, 5).unwrap();
+
+        let too_few = vec![sharing.shares[0].clone(), sharing.shares[1].clone()];
+        assert_ne!(reconstruct(&too_few).unwrap(), secret);
+    }
+
+    #[test]
+    fn rejects_invalid_threshold() {
+        let secret = sample_secret();
+        assert!(split_key(&secret, 0, 5).is_err());
+        assert!(split_key(&secret, 6, 5).is_err());
+    }
+
+    #[test]
+    fn rejects_duplicate_share_indices() {
+        let secret = sample_secret();
+        let sharing = split_key(&secret, 2, 4).unwrap();
+        let duplicated = vec![sharing.shares[0].clone(), sharing.shares[0].clone()];
+        assert!(reconstruct(&duplicated).is_err());
+    }
+
+    #[test]
+    fn verifies_untampered_shares_against_commitments() {
+        let secret = sample_secret();
+        let sharing = split_key(&secret, 3, 5).unwrap();
+        for share in &sharing.shares {
+            assert!(verify_share(share, &sharing.commitments).is_ok());
+        }
+    }
+
+    #[test]
+    fn detects_tampered_share_value() {
+        let secret = sample_secret();
+        let sharing = split_key(&secret, 3, 5).unwrap();
+        let mut tampered = sharing.shares[0].clone();
+        tampered.value[0] ^= 0x01;
+        assert!(verify_share(&tampered, &sharing.commitments).is_err());
+    }
+
+    #[test]
+    fn detects_share_substituted_with_another_holders() {
+        let secret = sample_secret();
+        let sharing = split_key(&secret, 3, 5).unwrap();
+        // A share is still internally consistent (it has a valid commitment
+        // somewhere) but assigned to the wrong index -- e.g. a malicious
+        // custodian handing out someone else's share under their own index.
+        let mut substituted = sharing.shares[1].clone();
+        substituted.index = sharing.shares[0].index;
+        assert!(verify_share(&substituted, &sharing.commitments).is_err());
+    }
+}
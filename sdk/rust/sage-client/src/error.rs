@@ -56,6 +56,20 @@ pub enum Error {
     #[error("Client not initialized")]
     NotInitialized,
 
+    /// Streaming transport error
+    #[error("Stream error: {0}")]
+    Stream(String),
+
+    /// The circuit breaker for this request's authority (host:port) is open
+    #[error("Circuit open for {0}: too many recent failures")]
+    CircuitOpen(String),
+
+    /// A server response's signature didn't verify against the resolved
+    /// server public key, or its timestamp fell outside the allowed
+    /// clock-skew window
+    #[error("Invalid server signature")]
+    InvalidServerSignature,
+
     /// Generic error
     #[error("{0}")]
     Other(String),
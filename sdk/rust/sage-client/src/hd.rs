@@ -0,0 +1,170 @@
+//! BIP32/SLIP-0010 hierarchical deterministic key derivation for Ed25519
+//!
+//! Ed25519 only supports hardened child derivation (there is no public-key
+//! tweak analogous to secp256k1's, since the curve's cofactor rules out
+//! non-hardened derivation), so every path segment parsed here is implicitly
+//! hardened regardless of whether the caller writes the trailing `'`.
+
+use crate::error::{Error, Result};
+use crate::types::{KeyPair, KeyType, SecretBytes};
+use ed25519_dalek::{PublicKey as Ed25519PublicKey, SecretKey};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// Domain-separation key used for the master-seed HMAC, per SLIP-0010.
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+
+/// A parsed `m/44'/...'` derivation path.
+///
+/// Every index is hardened; the high bit of each entry in
+/// [`DerivationPath::indices`] encodes this, so
+/// `m/44'/0'` and `m/44'/0` parse to the same [`DerivationPath`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath {
+    indices: Vec<u32>,
+}
+
+/// The high bit that marks a BIP32 index as hardened.
+const HARDENED_BIT: u32 = 0x8000_0000;
+
+impl DerivationPath {
+    /// Parse a path string such as `"m/44'/613'/0'/0'"`.
+    ///
+    /// The leading `m` is required; each subsequent segment is a decimal
+    /// index with an optional trailing `'` (accepted but not required, since
+    /// Ed25519 hardens every level regardless).
+    pub fn parse(path: &str) -> Result<Self> {
+        let mut segments = path.split('/');
+        if segments.next() != Some("m") {
+            return Err(Error::Validation(format!(
+                "derivation path must start with \"m\": {}",
+                path
+            )));
+        }
+
+        let mut indices = Vec::new();
+        for segment in segments {
+            let digits = segment.trim_end_matches('\'');
+            let index: u32 = digits
+                .parse()
+                .map_err(|_| Error::Validation(format!("invalid path segment: {}", segment)))?;
+            if index & HARDENED_BIT != 0 {
+                return Err(Error::Validation(format!(
+                    "path segment out of range: {}",
+                    segment
+                )));
+            }
+            indices.push(index | HARDENED_BIT);
+        }
+
+        if indices.is_empty() {
+            return Err(Error::Validation(
+                "derivation path must contain at least one index".to_string(),
+            ));
+        }
+
+        Ok(Self { indices })
+    }
+
+    /// The hardened child indices, in derivation order.
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+}
+
+/// `I = HMAC-SHA512(key, data)`, split into a 32-byte left half (`I_L`) and
+/// a 32-byte right half (`I_R`, the chain code).
+fn hmac_sha512_split(key: &[u8], data: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = HmacSha512::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    let i = mac.finalize().into_bytes();
+
+    let mut key_out = [0u8; 32];
+    let mut chain_code = [0u8; 32];
+    key_out.copy_from_slice(&i[..32]);
+    chain_code.copy_from_slice(&i[32..]);
+    (key_out, chain_code)
+}
+
+/// The SLIP-0010 master key and chain code for an Ed25519 seed.
+fn master_key(seed: &[u8]) -> ([u8; 32], [u8; 32]) {
+    hmac_sha512_split(ED25519_SEED_KEY, seed)
+}
+
+/// One step of SLIP-0010 hardened child derivation:
+/// `I = HMAC-SHA512(chain_code, 0x00 || key || ser32(index))`.
+fn derive_child(key: &[u8; 32], chain_code: &[u8; 32], index: u32) -> ([u8; 32], [u8; 32]) {
+    let mut data = Vec::with_capacity(1 + 32 + 4);
+    data.push(0x00);
+    data.extend_from_slice(key);
+    data.extend_from_slice(&index.to_be_bytes());
+    hmac_sha512_split(chain_code, &data)
+}
+
+/// Derive the Ed25519 `(key, chain_code)` pair at `path` from `seed`.
+pub fn derive_path(seed: &[u8], path: &DerivationPath) -> ([u8; 32], [u8; 32]) {
+    let (mut key, mut chain_code) = master_key(seed);
+    for &index in path.indices() {
+        let (child_key, child_chain_code) = derive_child(&key, &chain_code, index);
+        key = child_key;
+        chain_code = child_chain_code;
+    }
+    (key, chain_code)
+}
+
+impl KeyPair {
+    /// Derive the Ed25519 `KeyPair` at `path` from a master `seed`, following
+    /// SLIP-0010. The same `(seed, path)` pair always yields the same keys,
+    /// so an agent's several registry keys can all be recovered from one
+    /// seed phrase without backing each one up separately.
+    pub fn derive(seed: &[u8], path: &DerivationPath) -> Result<Self> {
+        let (key, _chain_code) = derive_path(seed, path);
+        let secret = SecretKey::from_bytes(&key)
+            .map_err(|e| Error::Crypto(format!("Invalid secret key: {}", e)))?;
+        let public = Ed25519PublicKey::from(&secret);
+
+        Ok(KeyPair {
+            private_key: SecretBytes::new(secret.to_bytes().to_vec()),
+            public_key: public.to_bytes().to_vec(),
+            key_type: KeyType::Ed25519,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hardened_and_unmarked_segments_the_same() {
+        let a = DerivationPath::parse("m/44'/613'/0'/0'").unwrap();
+        let b = DerivationPath::parse("m/44/613/0/0").unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.indices(), &[44 | HARDENED_BIT, 613 | HARDENED_BIT, HARDENED_BIT, HARDENED_BIT]);
+    }
+
+    #[test]
+    fn rejects_paths_without_leading_m() {
+        assert!(DerivationPath::parse("44'/0'").is_err());
+    }
+
+    #[test]
+    fn derivation_is_deterministic() {
+        let seed = b"00000000000000000000000000000000";
+        let path = DerivationPath::parse("m/44'/613'/0'").unwrap();
+        let a = KeyPair::derive(seed, &path).unwrap();
+        let b = KeyPair::derive(seed, &path).unwrap();
+        assert_eq!(a.private_key, b.private_key);
+        assert_eq!(a.public_key, b.public_key);
+    }
+
+    #[test]
+    fn different_paths_yield_different_keys() {
+        let seed = b"00000000000000000000000000000000";
+        let a = KeyPair::derive(seed, &DerivationPath::parse("m/44'/613'/0'").unwrap()).unwrap();
+        let b = KeyPair::derive(seed, &DerivationPath::parse("m/44'/613'/1'").unwrap()).unwrap();
+        assert_ne!(a.public_key, b.public_key);
+    }
+}
@@ -0,0 +1,141 @@
+//! Base58 and Solana-style JSON keyfile serialization for [`KeyPair`]
+//!
+//! Solana tooling (`solana-keygen`, wallet exports) speaks base58 strings and
+//! JSON keyfiles holding a 64-byte `secret||public` array. These helpers let
+//! an existing Solana wallet file be loaded directly into a SAGE `KeyPair`
+//! and the same identity registered on-chain, without hand-rolled glue.
+
+use crate::error::{Error, Result};
+use crate::types::{KeyPair, KeyType, SecretBytes};
+use ed25519_dalek::{PublicKey as Ed25519PublicKey, SecretKey};
+use std::fs;
+use std::path::Path;
+
+impl KeyPair {
+    /// Base58-encode the 32-byte Ed25519 secret key.
+    pub fn to_base58_string(&self) -> Result<String> {
+        if self.key_type != KeyType::Ed25519 {
+            return Err(Error::Crypto(
+                "base58 encoding is only defined for Ed25519 key pairs".to_string(),
+            ));
+        }
+        Ok(bs58::encode(self.private_key.as_bytes()).into_string())
+    }
+
+    /// Recover an Ed25519 `KeyPair` from a base58-encoded 32-byte secret key.
+    pub fn from_base58_string(s: &str) -> Result<Self> {
+        let decoded = bs58::decode(s)
+            .into_vec()
+            .map_err(|e| Error::Crypto(format!("invalid base58 secret key: {}", e)))?;
+        let seed: [u8; 32] = decoded
+            .try_into()
+            .map_err(|_| Error::Crypto("Ed25519 secret key must be 32 bytes".to_string()))?;
+
+        let secret = SecretKey::from_bytes(&seed)
+            .map_err(|e| Error::Crypto(format!("invalid Ed25519 secret key: {}", e)))?;
+        let public = Ed25519PublicKey::from(&secret);
+
+        Ok(KeyPair {
+            private_key: SecretBytes::new(seed.to_vec()),
+            public_key: public.to_bytes().to_vec(),
+            key_type: KeyType::Ed25519,
+        })
+    }
+
+    /// Write this keypair to `path` as a Solana-style JSON keyfile: a JSON
+    /// array of 64 bytes, `secret || public`.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        if self.key_type != KeyType::Ed25519 {
+            return Err(Error::Crypto(
+                "keyfile serialization is only defined for Ed25519 key pairs".to_string(),
+            ));
+        }
+
+        let mut bytes = Vec::with_capacity(64);
+        bytes.extend_from_slice(self.private_key.as_bytes());
+        bytes.extend_from_slice(&self.public_key);
+
+        let json = serde_json::to_string(&bytes).map_err(Error::Serialization)?;
+        fs::write(path, json).map_err(|e| Error::Other(format!("failed to write keyfile: {}", e)))
+    }
+
+    /// Read a Solana-style JSON keyfile (a 64-byte `secret || public` array)
+    /// from `path`, recomputing the public key from the secret and rejecting
+    /// the file if it doesn't match what's stored.
+    pub fn read_from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| Error::Other(format!("failed to read keyfile: {}", e)))?;
+        let bytes: Vec<u8> =
+            serde_json::from_str(&contents).map_err(Error::Serialization)?;
+
+        if bytes.len() != 64 {
+            return Err(Error::Crypto(format!(
+                "keyfile must contain 64 bytes, found {}",
+                bytes.len()
+            )));
+        }
+
+        let seed: [u8; 32] = bytes[..32].try_into().expect("checked length above");
+        let stored_public = &bytes[32..];
+
+        let secret = SecretKey::from_bytes(&seed)
+            .map_err(|e| Error::Crypto(format!("invalid Ed25519 secret key: {}", e)))?;
+        let recomputed_public = Ed25519PublicKey::from(&secret).to_bytes();
+
+        if recomputed_public.as_slice() != stored_public {
+            return Err(Error::Validation(
+                "keyfile public key does not match its secret key".to_string(),
+            ));
+        }
+
+        Ok(KeyPair {
+            private_key: SecretBytes::new(seed.to_vec()),
+            public_key: recomputed_public.to_vec(),
+            key_type: KeyType::Ed25519,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::Crypto;
+
+    #[test]
+    fn base58_round_trips() {
+        let keypair = Crypto::generate_ed25519_keypair().unwrap();
+        let encoded = keypair.to_base58_string().unwrap();
+        let decoded = KeyPair::from_base58_string(&encoded).unwrap();
+        assert_eq!(keypair.private_key, decoded.private_key);
+        assert_eq!(keypair.public_key, decoded.public_key);
+    }
+
+    #[test]
+    fn keyfile_round_trips() {
+        let keypair = Crypto::generate_ed25519_keypair().unwrap();
+        let path = std::env::temp_dir().join(format!("sage-keyfile-test-{}.json", std::process::id()));
+
+        keypair.write_to_file(&path).unwrap();
+        let loaded = KeyPair::read_from_file(&path).unwrap();
+
+        assert_eq!(keypair.private_key, loaded.private_key);
+        assert_eq!(keypair.public_key, loaded.public_key);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn keyfile_rejects_mismatched_public_key() {
+        let keypair = Crypto::generate_ed25519_keypair().unwrap();
+        let other = Crypto::generate_ed25519_keypair().unwrap();
+        let path = std::env::temp_dir().join(format!("sage-keyfile-bad-{}.json", std::process::id()));
+
+        let mut bytes = Vec::with_capacity(64);
+        bytes.extend_from_slice(keypair.private_key.as_bytes());
+        bytes.extend_from_slice(&other.public_key);
+        std::fs::write(&path, serde_json::to_string(&bytes).unwrap()).unwrap();
+
+        assert!(KeyPair::read_from_file(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -34,21 +34,39 @@
 //! }
 //! ```
 
+pub mod brain_key;
+pub mod cert;
+pub mod circuit_breaker;
 pub mod client;
 pub mod crypto;
+pub mod custody;
 pub mod did;
 pub mod error;
+pub mod hd;
+pub mod keyfile;
+pub mod registry;
 pub mod session;
+pub mod signer;
+pub mod store;
+pub mod stream;
 pub mod types;
 
+pub use cert::{issue_certificate, verify_certificate, AgentCertificate, Attestation};
+pub use circuit_breaker::CircuitBreaker;
 pub use client::{Client, ClientConfig};
 pub use crypto::Crypto;
 pub use did::{Did, DidDocument};
 pub use error::{Error, Result};
+pub use hd::DerivationPath;
+pub use registry::{EthRegistry, ResolvedServerIdentity, ServerIdentitySource, ServerRegistry};
 pub use session::{Session, SessionManager};
+pub use signer::{KeyPairSigner, Signer, UnixSocketSigner};
+pub use store::{
+    FlushMode, KvBackend, KvSessionStore, MemoryStore, SessionRecord, SessionStore, SqliteStore,
+};
 pub use types::{
     AgentMetadata, HandshakeRequest, HandshakeResponse, HealthStatus, KeyPair, Message,
-    MessageRequest, MessageResponse,
+    MessageRequest, MessageResponse, SecretBytes,
 };
 
 /// Library version
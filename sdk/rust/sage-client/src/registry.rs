@@ -0,0 +1,72 @@
+//! On-chain resolution of a server's registered signing/KEM keys
+//!
+//! `Client::handshake` needs to know which keys to treat as the server's
+//! true identity. The `/debug/kem-pub` and `/debug/server-did` endpoints are
+//! explicitly development-only and trust whatever the HTTP peer claims, so
+//! this adds a [`ServerRegistry`] trait — mirroring the [`SessionStore`]
+//! pattern in [`crate::store`] — with an [`EthRegistry`] implementation that
+//! resolves keys from the on-chain `SageRegistryV2` contract via
+//! `sage_contracts::client::SageClient`, binding the encrypted channel to
+//! blockchain-anchored key material instead of the transport.
+//!
+//! [`SessionStore`]: crate::store::SessionStore
+
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use ethers::providers::Middleware;
+use sage_contracts::client::SageClient;
+use std::sync::Arc;
+
+/// A server's registered signing and KEM public keys.
+#[derive(Debug, Clone)]
+pub struct ResolvedServerIdentity {
+    pub public_key: Vec<u8>,
+    pub public_kem_key: Vec<u8>,
+}
+
+/// Resolves a `did:sage:...` server identity to its registered public keys.
+#[async_trait]
+pub trait ServerRegistry: Send + Sync {
+    /// Look up `server_did`'s currently registered keys.
+    async fn resolve(&self, server_did: &str) -> Result<ResolvedServerIdentity>;
+}
+
+/// Resolves server identities from the on-chain `SageRegistryV2` contract.
+pub struct EthRegistry<M: Middleware> {
+    client: Arc<SageClient<M>>,
+}
+
+impl<M: Middleware> EthRegistry<M> {
+    /// Wrap an existing `SageClient` for use as a [`ServerRegistry`].
+    pub fn new(client: Arc<SageClient<M>>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + Send + Sync + 'static> ServerRegistry for EthRegistry<M> {
+    async fn resolve(&self, server_did: &str) -> Result<ResolvedServerIdentity> {
+        let agent = self
+            .client
+            .get_agent_by_did(server_did.to_string())
+            .await
+            .map_err(|e| Error::Did(format!("on-chain agent lookup failed: {}", e)))?;
+
+        Ok(ResolvedServerIdentity {
+            public_key: agent.public_key.to_vec(),
+            public_kem_key: agent.public_kem_key.to_vec(),
+        })
+    }
+}
+
+/// Where [`crate::client::Client`] resolves a server's identity from before
+/// starting a handshake.
+pub enum ServerIdentitySource {
+    /// Trust the development-only `/debug/kem-pub` and `/debug/server-pub`
+    /// endpoints served by the HTTP peer itself.
+    DebugEndpoints,
+    /// Resolve the server's registered keys from an on-chain registry (see
+    /// [`EthRegistry`]), so they're anchored to the blockchain rather than
+    /// the transport.
+    OnChain(Arc<dyn ServerRegistry>),
+}
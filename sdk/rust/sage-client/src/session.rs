@@ -2,19 +2,43 @@
 
 use crate::crypto::HpkeContext;
 use crate::error::{Error, Result};
-use chrono::{DateTime, Duration, Utc};
+use crate::store::{MemoryStore, SessionRecord, SessionStore};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use parking_lot::{Mutex, RwLock};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicI64, AtomicUsize, AtomicU64, Ordering};
+use std::sync::Arc;
 
-/// Session represents a secure session between client and server
+/// Number of independent shards backing a `SessionManager`.
+///
+/// Sessions are bucketed by a hash of their `session_id`, so two sessions in
+/// different shards can be read and mutated concurrently without contending
+/// on the same lock.
+const SHARD_COUNT: usize = 16;
+
+/// Session represents a secure session between client and server.
+///
+/// All fields that change after construction use interior mutability so that
+/// `encrypt`/`decrypt` only need `&self`: two threads operating on different
+/// sessions (or even the same session, serialized through `hpke_context`)
+/// never block on a manager-wide lock.
 pub struct Session {
     pub session_id: String,
     pub client_did: String,
     pub server_did: String,
-    pub hpke_context: HpkeContext,
+    hpke_context: Mutex<HpkeContext>,
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
-    pub last_activity: DateTime<Utc>,
-    pub message_count: u64,
+    last_activity: AtomicI64,
+    message_count: AtomicU64,
+    /// Causality/version token last read from the backing store (`0` if this
+    /// session has never been written to a versioned store, e.g. it was just
+    /// constructed). Carried so `SessionManager` can write through
+    /// `SessionStore::put_versioned` and detect a lost race instead of
+    /// silently clobbering a concurrent update.
+    version: AtomicU64,
 }
 
 impl Session {
@@ -31,11 +55,12 @@ impl Session {
             session_id,
             client_did,
             server_did,
-            hpke_context,
+            hpke_context: Mutex::new(hpke_context),
             created_at: now,
             expires_at: now + Duration::seconds(max_age_seconds),
-            last_activity: now,
-            message_count: 0,
+            last_activity: AtomicI64::new(now.timestamp()),
+            message_count: AtomicU64::new(0),
+            version: AtomicU64::new(0),
         }
     }
 
@@ -44,88 +69,256 @@ impl Session {
         Utc::now() > self.expires_at
     }
 
+    /// Timestamp of the last encrypt/decrypt attempt (or `update_activity`
+    /// call) on this session; it is stamped before sealing or opening, so a
+    /// failed attempt updates it too
+    pub fn last_activity(&self) -> DateTime<Utc> {
+        Utc.timestamp_opt(self.last_activity.load(Ordering::Acquire), 0)
+            .single()
+            .unwrap_or(self.created_at)
+    }
+
+    /// Number of messages sealed on this session so far
+    pub fn message_count(&self) -> u64 {
+        self.message_count.load(Ordering::Acquire)
+    }
+
+    /// Version last read from (or written to) the backing store; `0` if this
+    /// session was never written to a versioned store.
+    fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// Record the version a `put_versioned` call just confirmed, so the next
+    /// write carries the right `expected_version`.
+    fn set_version(&self, version: u64) {
+        self.version.store(version, Ordering::Release);
+    }
+
     /// Update last activity timestamp
-    pub fn update_activity(&mut self) -> Result<()> {
+    pub fn update_activity(&self) -> Result<()> {
         if self.is_expired() {
             return Err(Error::SessionExpired(self.session_id.clone()));
         }
-        self.last_activity = Utc::now();
+        self.last_activity.store(Utc::now().timestamp(), Ordering::Release);
         Ok(())
     }
 
     /// Encrypt message using session context
-    pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<Vec<u8>> {
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
         if self.is_expired() {
             return Err(Error::SessionExpired(self.session_id.clone()));
         }
         self.update_activity()?;
-        self.message_count += 1;
-        self.hpke_context.seal(plaintext)
+        self.message_count.fetch_add(1, Ordering::AcqRel);
+        self.hpke_context.lock().seal(plaintext)
     }
 
     /// Decrypt message using session context
-    pub fn decrypt(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
         if self.is_expired() {
             return Err(Error::SessionExpired(self.session_id.clone()));
         }
         self.update_activity()?;
-        self.hpke_context.open(ciphertext)
+        self.hpke_context.lock().open(ciphertext)
+    }
+
+    /// Snapshot this session into a [`SessionRecord`] suitable for handing to
+    /// a [`SessionStore`].
+    pub fn to_record(&self) -> SessionRecord {
+        let (hpke_key, hpke_sequence) = self.hpke_context.lock().export();
+        SessionRecord {
+            session_id: self.session_id.clone(),
+            client_did: self.client_did.clone(),
+            server_did: self.server_did.clone(),
+            hpke_key,
+            hpke_sequence,
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+            last_activity: self.last_activity(),
+            message_count: self.message_count(),
+            version: self.version(),
+        }
+    }
+
+    /// Rebuild a live `Session` from a durable record, e.g. when rehydrating
+    /// a `SessionManager` from a `SessionStore` on startup.
+    pub fn from_record(record: SessionRecord) -> Self {
+        Self {
+            session_id: record.session_id,
+            client_did: record.client_did,
+            server_did: record.server_did,
+            hpke_context: Mutex::new(HpkeContext::restore(record.hpke_key, record.hpke_sequence)),
+            created_at: record.created_at,
+            expires_at: record.expires_at,
+            last_activity: AtomicI64::new(record.last_activity.timestamp()),
+            message_count: AtomicU64::new(record.message_count),
+            version: AtomicU64::new(record.version),
+        }
     }
 }
 
-/// Session manager
+fn shard_index(session_id: &str) -> usize {
+    let mut hasher = DefaultHasher::new();
+    session_id.hash(&mut hasher);
+    (hasher.finish() as usize) % SHARD_COUNT
+}
+
+/// Thread-safe, shardable session manager.
+///
+/// Sessions live behind `SHARD_COUNT` independent `RwLock`s so that a server
+/// handling many concurrent handshakes never serializes on a single global
+/// lock. All methods take `&self`; share one `SessionManager` across request
+/// handlers (e.g. behind an `Arc`) instead of wrapping it in an outer `Mutex`.
 pub struct SessionManager {
-    sessions: HashMap<String, Session>,
+    shards: Vec<RwLock<HashMap<String, Arc<Session>>>>,
     max_sessions: usize,
+    cleanup_cursor: AtomicUsize,
+    store: Arc<dyn SessionStore>,
 }
 
 impl SessionManager {
-    /// Create new session manager
+    /// Create new session manager backed by the default in-memory store.
+    ///
+    /// Sessions created this way do not survive a restart; use
+    /// [`SessionManager::with_store`] for a durable backend.
     pub fn new(max_sessions: usize) -> Self {
+        Self::with_store(max_sessions, Arc::new(MemoryStore::new()))
+    }
+
+    /// Create a session manager backed by `store`, rehydrating any
+    /// non-expired sessions the store already holds (e.g. after a restart).
+    pub fn with_store(max_sessions: usize, store: Arc<dyn SessionStore>) -> Self {
+        let shards: Vec<_> = (0..SHARD_COUNT).map(|_| RwLock::new(HashMap::new())).collect();
+
+        if let Ok(live) = store.iter_live(Utc::now()) {
+            for record in live {
+                let session = Arc::new(Session::from_record(record));
+                let idx = shard_index(&session.session_id);
+                shards[idx].write().insert(session.session_id.clone(), session);
+            }
+        }
+
         Self {
-            sessions: HashMap::new(),
+            shards,
             max_sessions,
+            cleanup_cursor: AtomicUsize::new(0),
+            store,
         }
     }
 
-    /// Add session
-    pub fn add_session(&mut self, session: Session) -> Result<()> {
-        self.cleanup_expired();
+    fn shard(&self, session_id: &str) -> &RwLock<HashMap<String, Arc<Session>>> {
+        &self.shards[shard_index(session_id)]
+    }
 
-        if self.sessions.len() >= self.max_sessions {
+    /// Total number of sessions across all shards, without pruning expired ones
+    fn raw_len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.read().len()).sum()
+    }
+
+    /// Add session
+    pub fn add_session(&self, session: Session) -> Result<()> {
+        // Sweep expired sessions first so they don't count toward the cap;
+        // otherwise a burst of unswept expirations can spuriously reject new
+        // sessions with plenty of real headroom.
+        self.cleanup_all_expired();
+        let len = self.raw_len();
+        if len >= self.max_sessions {
             return Err(Error::Session(format!(
                 "Too many sessions ({}/{})",
-                self.sessions.len(),
-                self.max_sessions
+                len, self.max_sessions
             )));
         }
 
-        self.sessions
-            .insert(session.session_id.clone(), session);
+        let expected_version = match session.version() {
+            0 => None,
+            v => Some(v),
+        };
+        let new_version = self.store.put_versioned(session.to_record(), expected_version)?;
+        session.set_version(new_version);
+
+        let shard = self.shard(&session.session_id);
+        shard
+            .write()
+            .insert(session.session_id.clone(), Arc::new(session));
         Ok(())
     }
 
-    /// Get session by ID
-    pub fn get_session(&mut self, session_id: &str) -> Option<&mut Session> {
-        if let Some(session) = self.sessions.get_mut(session_id) {
-            if session.is_expired() {
-                self.sessions.remove(session_id);
-                return None;
+    /// Write the current in-memory state of a session back to the store,
+    /// via `put_versioned` so a write lost to a concurrent updater (e.g. a
+    /// peer instance sharing the same store) surfaces as a conflict instead
+    /// of silently clobbering it.
+    ///
+    /// Apart from `add_session`, this is the only call that writes a
+    /// session's state to the store; `encrypt`/`decrypt` do not. Call it
+    /// after activity that should survive a restart (e.g. periodically, or
+    /// after sensitive operations). With a store whose `FlushMode` is
+    /// `Periodic`, the write is buffered until the store's `flush()`.
+    pub fn checkpoint(&self, session_id: &str) -> Result<()> {
+        if let Some(session) = self.get_session(session_id) {
+            let expected_version = match session.version() {
+                0 => None,
+                v => Some(v),
+            };
+            let new_version = self.store.put_versioned(session.to_record(), expected_version)?;
+            session.set_version(new_version);
+        }
+        Ok(())
+    }
+
+    /// Get session by ID.
+    ///
+    /// Performs the expiry check and removal atomically under the shard's
+    /// write lock so a concurrent `get_session` can never observe a session
+    /// that is simultaneously being evicted.
+    pub fn get_session(&self, session_id: &str) -> Option<Arc<Session>> {
+        let shard = self.shard(session_id);
+        let mut guard = shard.write();
+        match guard.get(session_id) {
+            Some(session) if session.is_expired() => {
+                guard.remove(session_id);
+                None
             }
-            return Some(session);
+            Some(session) => Some(session.clone()),
+            None => None,
+        }
+    }
+
+    /// Load `session_id` from the backing store into this manager if it
+    /// isn't already resident, e.g. after this process restarted, or to
+    /// pick up a session a peer instance already established against the
+    /// same shared store. Returns whether a live session is resident
+    /// afterward.
+    pub fn resume(&self, session_id: &str) -> Result<bool> {
+        if self.get_session(session_id).is_some() {
+            return Ok(true);
         }
-        None
+
+        let record = match self.store.get(session_id)? {
+            Some(record) if record.expires_at > Utc::now() => record,
+            _ => return Ok(false),
+        };
+
+        self.add_session(Session::from_record(record))?;
+        Ok(true)
     }
 
     /// Remove session
-    pub fn remove_session(&mut self, session_id: &str) {
-        self.sessions.remove(session_id);
+    pub fn remove_session(&self, session_id: &str) {
+        self.shard(session_id).write().remove(session_id);
+        let _ = self.store.remove(session_id);
     }
 
-    /// Cleanup expired sessions
-    pub fn cleanup_expired(&mut self) -> usize {
-        let expired: Vec<String> = self
-            .sessions
+    /// Scan one shard (chosen round-robin) for expired sessions and evict
+    /// them, returning how many were removed.
+    ///
+    /// Scanning a single shard per call keeps this safe to run frequently
+    /// from a background task without ever holding every shard's lock at
+    /// once.
+    pub fn cleanup_expired(&self) -> usize {
+        let idx = self.cleanup_cursor.fetch_add(1, Ordering::Relaxed) % SHARD_COUNT;
+        let mut guard = self.shards[idx].write();
+        let expired: Vec<String> = guard
             .iter()
             .filter(|(_, session)| session.is_expired())
             .map(|(id, _)| id.clone())
@@ -133,20 +326,28 @@ impl SessionManager {
 
         let count = expired.len();
         for id in expired {
-            self.sessions.remove(&id);
+            guard.remove(&id);
+            let _ = self.store.remove(&id);
         }
         count
     }
 
+    /// Sweep every shard for expired sessions, returning the total removed.
+    pub fn cleanup_all_expired(&self) -> usize {
+        (0..SHARD_COUNT).map(|_| self.cleanup_expired()).sum()
+    }
+
     /// Count active sessions
-    pub fn count(&mut self) -> usize {
-        self.cleanup_expired();
-        self.sessions.len()
+    pub fn count(&self) -> usize {
+        self.cleanup_all_expired();
+        self.raw_len()
     }
 
     /// Clear all sessions
-    pub fn clear(&mut self) {
-        self.sessions.clear();
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            shard.write().clear();
+        }
     }
 }
 
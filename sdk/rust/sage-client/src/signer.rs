@@ -0,0 +1,138 @@
+//! Pluggable identity signer
+//!
+//! `Client` used to hold `identity_keypair: Option<KeyPair>` and call
+//! `Crypto::sign` directly, which means the private key always lives in the
+//! client's own process memory. The [`Signer`] trait decouples "produce a
+//! signature over these bytes" from "hold the private key", so the key can
+//! instead live in a separate hardened process or a hardware token.
+//! [`KeyPairSigner`] is the default, in-process implementation;
+//! [`UnixSocketSigner`] talks to an external key agent over a Unix-domain
+//! socket, analogous to the ssh-agent protocol.
+
+use crate::crypto::Crypto;
+use crate::error::{Error, Result};
+use crate::types::KeyPair;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+/// Something that can produce Ed25519 signatures for an identity, without
+/// necessarily exposing the private key to the caller.
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// Sign `message`, returning the raw Ed25519 signature bytes.
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>>;
+
+    /// The signer's Ed25519 public key.
+    fn public_key(&self) -> &[u8];
+}
+
+/// Signs in-process using an existing [`KeyPair`].
+///
+/// This is the default: the private key lives in this process's memory,
+/// same as before `Signer` existed.
+pub struct KeyPairSigner(KeyPair);
+
+impl KeyPairSigner {
+    pub fn new(keypair: KeyPair) -> Self {
+        Self(keypair)
+    }
+}
+
+#[async_trait]
+impl Signer for KeyPairSigner {
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        Crypto::sign(message, &self.0.private_key)
+    }
+
+    fn public_key(&self) -> &[u8] {
+        &self.0.public_key
+    }
+}
+
+/// Request frame sent to an external key agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum AgentRequest {
+    /// Ask the agent for the identity's public key.
+    PublicKey,
+    /// Ask the agent to sign `message`.
+    Sign { message: Vec<u8> },
+}
+
+/// Response frame returned by an external key agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum AgentResponse {
+    PublicKey { public_key: Vec<u8> },
+    Signature { signature: Vec<u8> },
+    Error { message: String },
+}
+
+/// Signs by delegating to an external key agent reachable over a
+/// Unix-domain socket, one newline-delimited JSON request/response per call
+/// — the private key never enters this process.
+pub struct UnixSocketSigner {
+    socket_path: PathBuf,
+    public_key: Vec<u8>,
+}
+
+impl UnixSocketSigner {
+    /// Connect to the key agent listening on `socket_path` and fetch its
+    /// public key.
+    pub async fn connect(socket_path: impl Into<PathBuf>) -> Result<Self> {
+        let socket_path = socket_path.into();
+        let public_key = match Self::roundtrip(&socket_path, &AgentRequest::PublicKey).await? {
+            AgentResponse::PublicKey { public_key } => public_key,
+            AgentResponse::Error { message } => return Err(Error::Crypto(message)),
+            _ => return Err(Error::Crypto("unexpected key agent response".to_string())),
+        };
+
+        Ok(Self {
+            socket_path,
+            public_key,
+        })
+    }
+
+    async fn roundtrip(socket_path: &Path, request: &AgentRequest) -> Result<AgentResponse> {
+        let stream = UnixStream::connect(socket_path)
+            .await
+            .map_err(|e| Error::Crypto(format!("key agent connection failed: {}", e)))?;
+        let (read_half, mut write_half) = stream.into_split();
+
+        let mut line = serde_json::to_vec(request)?;
+        line.push(b'\n');
+        write_half
+            .write_all(&line)
+            .await
+            .map_err(|e| Error::Crypto(format!("key agent write failed: {}", e)))?;
+
+        let mut response_line = String::new();
+        BufReader::new(read_half)
+            .read_line(&mut response_line)
+            .await
+            .map_err(|e| Error::Crypto(format!("key agent read failed: {}", e)))?;
+
+        Ok(serde_json::from_str(&response_line)?)
+    }
+}
+
+#[async_trait]
+impl Signer for UnixSocketSigner {
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let request = AgentRequest::Sign {
+            message: message.to_vec(),
+        };
+        match Self::roundtrip(&self.socket_path, &request).await? {
+            AgentResponse::Signature { signature } => Ok(signature),
+            AgentResponse::Error { message } => Err(Error::Crypto(message)),
+            _ => Err(Error::Crypto("unexpected key agent response".to_string())),
+        }
+    }
+
+    fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
@@ -0,0 +1,608 @@
+//! Pluggable persistence backends for session state
+//!
+//! `SessionManager` delegates all durability concerns to a [`SessionStore`]
+//! implementation so a server can keep the default in-memory behavior or
+//! swap in [`SqliteStore`] to survive restarts without losing established
+//! HPKE sessions.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Durable snapshot of a session, sufficient to rehydrate it after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_id: String,
+    pub client_did: String,
+    pub server_did: String,
+    /// Raw HPKE symmetric key material, as exported by `HpkeContext::export`.
+    pub hpke_key: Vec<u8>,
+    pub hpke_sequence: u64,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+    pub message_count: u64,
+    /// Causality/version token assigned by the backing store. Backends that
+    /// don't implement optimistic concurrency (`MemoryStore`, `SqliteStore`)
+    /// leave this at `0`; [`KvSessionStore`] fills it in from the value its
+    /// [`KvBackend`] returns.
+    #[serde(default)]
+    pub version: u64,
+}
+
+impl SessionRecord {
+    fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        now > self.expires_at
+    }
+}
+
+/// How writes reach the backing store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushMode {
+    /// Every mutating call blocks until the backend confirms the write.
+    /// Safest, but bottlenecks high-throughput servers on disk I/O.
+    WriteThrough,
+    /// Mutations are buffered in memory and only reach disk when `flush()`
+    /// is called (e.g. from a periodic background task).
+    Periodic,
+}
+
+/// Backend trait for persisting session state.
+///
+/// Implementations must be safe to share across threads: `SessionManager`
+/// holds one instance behind an `Arc` and calls it from any request handler.
+pub trait SessionStore: Send + Sync {
+    /// Insert or overwrite a record.
+    fn put(&self, record: SessionRecord) -> Result<()>;
+    /// Insert or update `record`, enforcing optimistic concurrency:
+    /// `expected_version` must match the version the backend currently has
+    /// on file for `record.session_id` (`None` meaning the caller believes
+    /// the key doesn't exist yet). Returns the record's new version on
+    /// success, or `Err(Error::Session(..))` if another writer's update won
+    /// the race, so the caller should re-read and retry.
+    ///
+    /// The default implementation ignores versioning entirely and always
+    /// succeeds — correct for single-writer backends like `MemoryStore` and
+    /// `SqliteStore`, where nothing else is mutating the same key
+    /// concurrently. [`KvSessionStore`] overrides this with a real
+    /// compare-and-swap.
+    fn put_versioned(&self, record: SessionRecord, expected_version: Option<u64>) -> Result<u64> {
+        let _ = expected_version;
+        self.put(record)?;
+        Ok(0)
+    }
+    /// Fetch a record by id, regardless of whether it has expired.
+    fn get(&self, session_id: &str) -> Result<Option<SessionRecord>>;
+    /// Remove a record, if present.
+    fn remove(&self, session_id: &str) -> Result<()>;
+    /// Records that are expired as of `now`.
+    fn iter_expired(&self, now: DateTime<Utc>) -> Result<Vec<SessionRecord>>;
+    /// Remove every expired record, returning how many were purged.
+    fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize>;
+    /// All non-expired records, used to rehydrate a manager on startup.
+    fn iter_live(&self, now: DateTime<Utc>) -> Result<Vec<SessionRecord>>;
+    /// Flush any buffered writes. A no-op for write-through backends.
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Default in-memory backend, equivalent to the original `HashMap`-only
+/// manager: nothing survives a restart.
+#[derive(Default)]
+pub struct MemoryStore {
+    records: Mutex<HashMap<String, SessionRecord>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SessionStore for MemoryStore {
+    fn put(&self, record: SessionRecord) -> Result<()> {
+        self.records.lock().unwrap().insert(record.session_id.clone(), record);
+        Ok(())
+    }
+
+    fn get(&self, session_id: &str) -> Result<Option<SessionRecord>> {
+        Ok(self.records.lock().unwrap().get(session_id).cloned())
+    }
+
+    fn remove(&self, session_id: &str) -> Result<()> {
+        self.records.lock().unwrap().remove(session_id);
+        Ok(())
+    }
+
+    fn iter_expired(&self, now: DateTime<Utc>) -> Result<Vec<SessionRecord>> {
+        Ok(self
+            .records
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|r| r.is_expired_at(now))
+            .cloned()
+            .collect())
+    }
+
+    fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize> {
+        let mut records = self.records.lock().unwrap();
+        let expired: Vec<String> = records
+            .values()
+            .filter(|r| r.is_expired_at(now))
+            .map(|r| r.session_id.clone())
+            .collect();
+        let count = expired.len();
+        for id in expired {
+            records.remove(&id);
+        }
+        Ok(count)
+    }
+
+    fn iter_live(&self, now: DateTime<Utc>) -> Result<Vec<SessionRecord>> {
+        Ok(self
+            .records
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|r| !r.is_expired_at(now))
+            .cloned()
+            .collect())
+    }
+}
+
+/// SQLite-backed store that persists session metadata and HPKE keying
+/// material to disk, so a crashed server can rehydrate its live sessions on
+/// restart instead of forcing every agent to re-handshake.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+    mode: FlushMode,
+    pending: Mutex<HashMap<String, SessionRecord>>,
+}
+
+impl SqliteStore {
+    /// Open (creating if needed) a SQLite-backed store at `path`.
+    pub fn open(path: impl AsRef<Path>, mode: FlushMode) -> Result<Self> {
+        let conn = Connection::open(path)
+            .map_err(|e| Error::Other(format!("failed to open session store: {}", e)))?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS sessions (
+                session_id     TEXT PRIMARY KEY,
+                client_did     TEXT NOT NULL,
+                server_did     TEXT NOT NULL,
+                hpke_key       BLOB NOT NULL,
+                hpke_sequence  INTEGER NOT NULL,
+                created_at     INTEGER NOT NULL,
+                expires_at     INTEGER NOT NULL,
+                last_activity  INTEGER NOT NULL,
+                message_count  INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| Error::Other(format!("failed to create sessions table: {}", e)))?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            mode,
+            pending: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn write_row(conn: &Connection, record: &SessionRecord) -> Result<()> {
+        conn.execute(
+            "INSERT INTO sessions
+                (session_id, client_did, server_did, hpke_key, hpke_sequence,
+                 created_at, expires_at, last_activity, message_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(session_id) DO UPDATE SET
+                client_did = excluded.client_did,
+                server_did = excluded.server_did,
+                hpke_key = excluded.hpke_key,
+                hpke_sequence = excluded.hpke_sequence,
+                created_at = excluded.created_at,
+                expires_at = excluded.expires_at,
+                last_activity = excluded.last_activity,
+                message_count = excluded.message_count",
+            params![
+                record.session_id,
+                record.client_did,
+                record.server_did,
+                record.hpke_key,
+                record.hpke_sequence as i64,
+                record.created_at.timestamp(),
+                record.expires_at.timestamp(),
+                record.last_activity.timestamp(),
+                record.message_count as i64,
+            ],
+        )
+        .map_err(|e| Error::Other(format!("failed to write session row: {}", e)))?;
+        Ok(())
+    }
+
+    fn row_to_record(row: &rusqlite::Row<'_>) -> rusqlite::Result<SessionRecord> {
+        use chrono::TimeZone;
+
+        let ts = |secs: i64| Utc.timestamp_opt(secs, 0).single().unwrap_or_else(Utc::now);
+
+        Ok(SessionRecord {
+            session_id: row.get(0)?,
+            client_did: row.get(1)?,
+            server_did: row.get(2)?,
+            hpke_key: row.get(3)?,
+            hpke_sequence: row.get::<_, i64>(4)? as u64,
+            created_at: ts(row.get(5)?),
+            expires_at: ts(row.get(6)?),
+            last_activity: ts(row.get(7)?),
+            message_count: row.get::<_, i64>(8)? as u64,
+            version: 0,
+        })
+    }
+}
+
+impl SessionStore for SqliteStore {
+    fn put(&self, record: SessionRecord) -> Result<()> {
+        match self.mode {
+            FlushMode::WriteThrough => Self::write_row(&self.conn.lock().unwrap(), &record),
+            FlushMode::Periodic => {
+                self.pending.lock().unwrap().insert(record.session_id.clone(), record);
+                Ok(())
+            }
+        }
+    }
+
+    fn get(&self, session_id: &str) -> Result<Option<SessionRecord>> {
+        if let Some(record) = self.pending.lock().unwrap().get(session_id) {
+            return Ok(Some(record.clone()));
+        }
+
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT session_id, client_did, server_did, hpke_key, hpke_sequence,
+                    created_at, expires_at, last_activity, message_count
+             FROM sessions WHERE session_id = ?1",
+            params![session_id],
+            Self::row_to_record,
+        )
+        .map(Some)
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            e => Err(Error::Other(format!("failed to read session row: {}", e))),
+        })
+    }
+
+    fn remove(&self, session_id: &str) -> Result<()> {
+        self.pending.lock().unwrap().remove(session_id);
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("DELETE FROM sessions WHERE session_id = ?1", params![session_id])
+            .map_err(|e| Error::Other(format!("failed to delete session row: {}", e)))?;
+        Ok(())
+    }
+
+    fn iter_expired(&self, now: DateTime<Utc>) -> Result<Vec<SessionRecord>> {
+        self.flush()?;
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT session_id, client_did, server_did, hpke_key, hpke_sequence,
+                        created_at, expires_at, last_activity, message_count
+                 FROM sessions WHERE expires_at < ?1",
+            )
+            .map_err(|e| Error::Other(format!("failed to prepare query: {}", e)))?;
+        let rows = stmt
+            .query_map(params![now.timestamp()], Self::row_to_record)
+            .map_err(|e| Error::Other(format!("failed to run query: {}", e)))?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| Error::Other(format!("failed to read expired rows: {}", e)))
+    }
+
+    fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize> {
+        self.flush()?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM sessions WHERE expires_at < ?1", params![now.timestamp()])
+            .map_err(|e| Error::Other(format!("failed to purge expired rows: {}", e)))
+    }
+
+    fn iter_live(&self, now: DateTime<Utc>) -> Result<Vec<SessionRecord>> {
+        self.flush()?;
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT session_id, client_did, server_did, hpke_key, hpke_sequence,
+                        created_at, expires_at, last_activity, message_count
+                 FROM sessions WHERE expires_at >= ?1",
+            )
+            .map_err(|e| Error::Other(format!("failed to prepare query: {}", e)))?;
+        let rows = stmt
+            .query_map(params![now.timestamp()], Self::row_to_record)
+            .map_err(|e| Error::Other(format!("failed to run query: {}", e)))?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| Error::Other(format!("failed to read live rows: {}", e)))
+    }
+
+    fn flush(&self) -> Result<()> {
+        if self.mode != FlushMode::Periodic {
+            return Ok(());
+        }
+        let mut pending = self.pending.lock().unwrap();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let conn = self.conn.lock().unwrap();
+        for record in pending.values() {
+            Self::write_row(&conn, record)?;
+        }
+        pending.clear();
+        Ok(())
+    }
+}
+
+/// Abstraction over an external key-value store's wire protocol, so
+/// [`KvSessionStore`] doesn't depend on any one backend. A production
+/// deployment implements this against whichever store the fleet already
+/// runs (Redis, etcd, DynamoDB, ...), giving every `SessionManager` sharing
+/// that store a consistent view of each session.
+pub trait KvBackend: Send + Sync {
+    /// Fetch the raw bytes and causality/version token currently stored at
+    /// `key`.
+    fn get(&self, key: &str) -> Result<Option<(Vec<u8>, u64)>>;
+    /// Store `value` at `key` iff the backend's current version for `key`
+    /// equals `expected_version` (`None` meaning "`key` must not exist
+    /// yet"). Returns the new version on success, or
+    /// `Err(Error::Session(..))` if another writer's update won the race.
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        expected_version: Option<u64>,
+    ) -> Result<u64>;
+    /// Remove `key`, if present.
+    fn delete(&self, key: &str) -> Result<()>;
+    /// Every key currently present, used to rehydrate/purge the store.
+    fn keys(&self) -> Result<Vec<String>>;
+}
+
+/// Session store backed by an external key-value store via [`KvBackend`].
+///
+/// Unlike `MemoryStore`/`SqliteStore`, this backend is meant to be shared by
+/// multiple `SessionManager`s at once (e.g. a horizontally-scaled fleet of
+/// agents), so every write goes through `compare_and_swap`: a `put_versioned`
+/// call carries the version the caller last read and loses the race,
+/// forcing a re-read, if another instance updated the session first.
+pub struct KvSessionStore<B: KvBackend> {
+    backend: B,
+}
+
+impl<B: KvBackend> KvSessionStore<B> {
+    pub fn new(backend: B) -> Self {
+        Self { backend }
+    }
+
+    fn decode(bytes: &[u8], version: u64) -> Result<SessionRecord> {
+        let mut record: SessionRecord = serde_json::from_slice(bytes)?;
+        record.version = version;
+        Ok(record)
+    }
+
+    fn all_records(&self) -> Result<Vec<SessionRecord>> {
+        let mut records = Vec::new();
+        for key in self.backend.keys()? {
+            if let Some((bytes, version)) = self.backend.get(&key)? {
+                records.push(Self::decode(&bytes, version)?);
+            }
+        }
+        Ok(records)
+    }
+}
+
+impl<B: KvBackend> SessionStore for KvSessionStore<B> {
+    fn put(&self, record: SessionRecord) -> Result<()> {
+        let expected_version = self.backend.get(&record.session_id)?.map(|(_, v)| v);
+        self.put_versioned(record, expected_version)?;
+        Ok(())
+    }
+
+    fn put_versioned(&self, record: SessionRecord, expected_version: Option<u64>) -> Result<u64> {
+        let key = record.session_id.clone();
+        let bytes = serde_json::to_vec(&record)?;
+        self.backend.compare_and_swap(&key, bytes, expected_version)
+    }
+
+    fn get(&self, session_id: &str) -> Result<Option<SessionRecord>> {
+        match self.backend.get(session_id)? {
+            Some((bytes, version)) => Ok(Some(Self::decode(&bytes, version)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn remove(&self, session_id: &str) -> Result<()> {
+        self.backend.delete(session_id)
+    }
+
+    fn iter_expired(&self, now: DateTime<Utc>) -> Result<Vec<SessionRecord>> {
+        Ok(self
+            .all_records()?
+            .into_iter()
+            .filter(|r| r.is_expired_at(now))
+            .collect())
+    }
+
+    fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize> {
+        let expired = self.iter_expired(now)?;
+        let count = expired.len();
+        for record in expired {
+            self.backend.delete(&record.session_id)?;
+        }
+        Ok(count)
+    }
+
+    fn iter_live(&self, now: DateTime<Utc>) -> Result<Vec<SessionRecord>> {
+        Ok(self
+            .all_records()?
+            .into_iter()
+            .filter(|r| !r.is_expired_at(now))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn sample_record(id: &str, ttl_seconds: i64) -> SessionRecord {
+        let now = Utc::now();
+        SessionRecord {
+            session_id: id.to_string(),
+            client_did: "did:sage:ethereum:0xAlice".to_string(),
+            server_did: "did:sage:ethereum:0xServer".to_string(),
+            hpke_key: vec![0u8; 32],
+            hpke_sequence: 0,
+            created_at: now,
+            expires_at: now + Duration::seconds(ttl_seconds),
+            last_activity: now,
+            message_count: 0,
+            version: 0,
+        }
+    }
+
+    #[test]
+    fn memory_store_round_trips_and_purges_expired() {
+        let store = MemoryStore::new();
+        store.put(sample_record("live", 3600)).unwrap();
+        store.put(sample_record("dead", -10)).unwrap();
+
+        assert!(store.get("live").unwrap().is_some());
+
+        let now = Utc::now();
+        assert_eq!(store.iter_expired(now).unwrap().len(), 1);
+        assert_eq!(store.purge_expired(now).unwrap(), 1);
+        assert!(store.get("dead").unwrap().is_none());
+        assert!(store.get("live").unwrap().is_some());
+    }
+
+    #[test]
+    fn sqlite_store_survives_a_simulated_restart() {
+        let dir = std::env::temp_dir().join(format!("sage-session-store-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("sessions.sqlite3");
+        let _ = std::fs::remove_file(&db_path);
+
+        {
+            let store = SqliteStore::open(&db_path, FlushMode::WriteThrough).unwrap();
+            store.put(sample_record("crash-recoverable", 3600)).unwrap();
+        }
+
+        // Reopen to simulate the process restarting.
+        let store = SqliteStore::open(&db_path, FlushMode::WriteThrough).unwrap();
+        let rehydrated = store.get("crash-recoverable").unwrap();
+        assert!(rehydrated.is_some());
+        assert_eq!(rehydrated.unwrap().session_id, "crash-recoverable");
+
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    #[test]
+    fn periodic_mode_only_persists_on_flush() {
+        let dir = std::env::temp_dir().join(format!("sage-session-store-test-periodic-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("sessions.sqlite3");
+        let _ = std::fs::remove_file(&db_path);
+
+        let store = SqliteStore::open(&db_path, FlushMode::Periodic).unwrap();
+        store.put(sample_record("buffered", 3600)).unwrap();
+        assert!(store.get("buffered").unwrap().is_some());
+
+        store.flush().unwrap();
+        assert_eq!(store.iter_live(Utc::now()).unwrap().len(), 1);
+
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    /// Minimal in-process stand-in for a real KV backend, used only to
+    /// exercise `KvSessionStore`'s compare-and-swap logic in tests.
+    #[derive(Default)]
+    struct InMemoryKvBackend {
+        entries: Mutex<HashMap<String, (Vec<u8>, u64)>>,
+    }
+
+    impl KvBackend for InMemoryKvBackend {
+        fn get(&self, key: &str) -> Result<Option<(Vec<u8>, u64)>> {
+            Ok(self.entries.lock().unwrap().get(key).cloned())
+        }
+
+        fn compare_and_swap(
+            &self,
+            key: &str,
+            value: Vec<u8>,
+            expected_version: Option<u64>,
+        ) -> Result<u64> {
+            let mut entries = self.entries.lock().unwrap();
+            let current_version = entries.get(key).map(|(_, v)| *v);
+            if current_version != expected_version {
+                return Err(Error::Session(format!(
+                    "version conflict for {}: expected {:?}, found {:?}",
+                    key, expected_version, current_version
+                )));
+            }
+            let new_version = current_version.unwrap_or(0) + 1;
+            entries.insert(key.to_string(), (value, new_version));
+            Ok(new_version)
+        }
+
+        fn delete(&self, key: &str) -> Result<()> {
+            self.entries.lock().unwrap().remove(key);
+            Ok(())
+        }
+
+        fn keys(&self) -> Result<Vec<String>> {
+            Ok(self.entries.lock().unwrap().keys().cloned().collect())
+        }
+    }
+
+    #[test]
+    fn kv_store_round_trips_through_the_backend() {
+        let store = KvSessionStore::new(InMemoryKvBackend::default());
+        store.put(sample_record("live", 3600)).unwrap();
+
+        let fetched = store.get("live").unwrap().unwrap();
+        assert_eq!(fetched.session_id, "live");
+        assert_eq!(fetched.version, 1);
+    }
+
+    #[test]
+    fn kv_store_rejects_a_stale_version() {
+        let store = KvSessionStore::new(InMemoryKvBackend::default());
+        let version = store
+            .put_versioned(sample_record("contended", 3600), None)
+            .unwrap();
+
+        // A writer that read the session before this update retries with
+        // the version it last saw (`None`), and loses the race.
+        let stale_write = store.put_versioned(sample_record("contended", 3600), None);
+        assert!(stale_write.is_err());
+
+        // The writer that read the latest version succeeds.
+        let fresh_write =
+            store.put_versioned(sample_record("contended", 3600), Some(version));
+        assert!(fresh_write.is_ok());
+    }
+
+    #[test]
+    fn kv_store_purges_expired_entries() {
+        let store = KvSessionStore::new(InMemoryKvBackend::default());
+        store.put(sample_record("live", 3600)).unwrap();
+        store.put(sample_record("dead", -10)).unwrap();
+
+        let now = Utc::now();
+        assert_eq!(store.purge_expired(now).unwrap(), 1);
+        assert!(store.get("dead").unwrap().is_none());
+        assert!(store.get("live").unwrap().is_some());
+    }
+}
@@ -0,0 +1,148 @@
+//! Bidirectional streaming transport for session events
+//!
+//! The HTTP `send_message`/`handshake` flow is strictly request/response and
+//! can't push server-initiated events to an agent. This module opens a
+//! long-lived WebSocket connection for a `Session` and multiplexes three
+//! kinds of traffic over it: correlated requests, their responses, and
+//! unsolicited `event` frames the server pushes whenever it wants. Every
+//! frame is HPKE-sealed through the same `Session`, so ordering and the
+//! session's `message_count`/last-activity bookkeeping stay correct across
+//! the whole stream.
+
+use crate::error::{Error, Result};
+use crate::session::Session;
+use futures_util::stream::Stream;
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+/// Wire envelope distinguishing request/response/event frames sharing one
+/// connection. `sealed` is the HPKE-sealed payload produced by
+/// `Session::encrypt`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Envelope {
+    /// A caller-initiated request, correlated to its response by `request_id`.
+    Request { request_id: u64, sealed: Vec<u8> },
+    /// The server's reply to a `Request` with the same `request_id`.
+    Response { request_id: u64, sealed: Vec<u8> },
+    /// An unsolicited, server-pushed event with no corresponding request.
+    Event { sealed: Vec<u8> },
+}
+
+type PendingReplies = Arc<Mutex<HashMap<u64, oneshot::Sender<Vec<u8>>>>>;
+
+/// A live subscription over the streaming connection: issues correlated
+/// request/response calls and hands out decrypted push events via
+/// [`EventStream`].
+pub struct StreamHandle {
+    session: Arc<Session>,
+    outbound: mpsc::UnboundedSender<WsMessage>,
+    next_request_id: AtomicU64,
+    pending: PendingReplies,
+}
+
+/// A `Stream` of decrypted, server-pushed events for a subscribed session.
+pub struct EventStream {
+    receiver: mpsc::UnboundedReceiver<Vec<u8>>,
+}
+
+impl Stream for EventStream {
+    type Item = Vec<u8>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl StreamHandle {
+    /// Open a streaming connection to `url` for `session`, returning a handle
+    /// for correlated requests and a `Stream` of decrypted push events.
+    pub async fn connect(url: &str, session: Arc<Session>) -> Result<(Self, EventStream)> {
+        let (ws_stream, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .map_err(|e| Error::Stream(e.to_string()))?;
+        let (mut ws_write, mut ws_read) = ws_stream.split();
+
+        let (outbound_tx, mut outbound_rx) = mpsc::unbounded_channel::<WsMessage>();
+        let (event_tx, event_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+        let pending: PendingReplies = Arc::new(Mutex::new(HashMap::new()));
+
+        // Drive outbound frames from the channel onto the socket.
+        tokio::spawn(async move {
+            while let Some(msg) = outbound_rx.recv().await {
+                if ws_write.send(msg).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        // Dispatch inbound frames: route responses to their waiting caller,
+        // decrypt events and forward them, and drop inbound requests and
+        // non-binary frames.
+        let read_session = session.clone();
+        let read_pending = pending.clone();
+        tokio::spawn(async move {
+            while let Some(Ok(frame)) = ws_read.next().await {
+                let WsMessage::Binary(bytes) = frame else {
+                    continue;
+                };
+                let Ok(envelope) = serde_json::from_slice::<Envelope>(&bytes) else {
+                    continue;
+                };
+                match envelope {
+                    Envelope::Response { request_id, sealed } => {
+                        if let Ok(plaintext) = read_session.decrypt(&sealed) {
+                            if let Some(tx) = read_pending.lock().remove(&request_id) {
+                                let _ = tx.send(plaintext);
+                            }
+                        }
+                    }
+                    Envelope::Event { sealed } => {
+                        if let Ok(plaintext) = read_session.decrypt(&sealed) {
+                            let _ = event_tx.send(plaintext);
+                        }
+                    }
+                    Envelope::Request { .. } => {
+                        // Servers don't expect clients to answer inbound
+                        // requests in this protocol version; ignore.
+                    }
+                }
+            }
+        });
+
+        Ok((
+            Self {
+                session,
+                outbound: outbound_tx,
+                next_request_id: AtomicU64::new(0),
+                pending,
+            },
+            EventStream { receiver: event_rx },
+        ))
+    }
+
+    /// Issue a request over the stream and await its correlated response.
+    pub async fn request(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let request_id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        let sealed = self.session.encrypt(plaintext)?;
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().insert(request_id, tx);
+
+        let envelope = Envelope::Request { request_id, sealed };
+        let frame = serde_json::to_vec(&envelope)?;
+        self.outbound
+            .send(WsMessage::Binary(frame))
+            .map_err(|_| Error::Other("streaming connection closed".to_string()))?;
+
+        rx.await.map_err(|_| Error::Other("streaming connection closed before a reply arrived".to_string()))
+    }
+}
@@ -3,12 +3,57 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Secret key bytes that are wiped on drop instead of lingering on the heap.
+///
+/// `Deref`s to `[u8]` so it can be passed anywhere a `&[u8]` is expected
+/// (e.g. `Crypto::sign(msg, &keypair.private_key)`).
+#[derive(Clone, PartialEq, Eq, Zeroize)]
+pub struct SecretBytes(Vec<u8>);
+
+impl SecretBytes {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Deref for SecretBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
 
 /// Cryptographic key pair
-#[derive(Debug, Clone)]
+///
+/// `private_key` zeroizes its buffer on drop and never prints its contents
+/// via `Debug`; only `public_key` and `key_type` are safe to log.
+#[derive(Debug, Clone, Zeroize, ZeroizeOnDrop)]
 pub struct KeyPair {
-    pub private_key: Vec<u8>,
+    pub private_key: SecretBytes,
+    #[zeroize(skip)]
     pub public_key: Vec<u8>,
+    #[zeroize(skip)]
     pub key_type: KeyType,
 }
 
@@ -17,6 +62,9 @@ pub struct KeyPair {
 pub enum KeyType {
     Ed25519,
     X25519,
+    /// secp256k1, compatible with Bitcoin/Ethereum-style wallets. Registered
+    /// on-chain as key type `1`; see the sage-registry program.
+    Secp256k1,
 }
 
 impl KeyType {
@@ -24,6 +72,7 @@ impl KeyType {
         match self {
             KeyType::Ed25519 => "Ed25519",
             KeyType::X25519 => "X25519",
+            KeyType::Secp256k1 => "Secp256k1",
         }
     }
 }
@@ -54,6 +103,8 @@ pub struct HandshakeRequest {
 pub struct HandshakeResponse {
     pub session_id: String,
     pub response: String, // Base64-encoded encrypted response
+    pub timestamp: i64,
+    pub signature: String, // Base64-encoded signature over server_did|client_did|response|timestamp
 }
 
 /// Message send request
@@ -72,6 +123,8 @@ pub struct MessageResponse {
     pub response: String, // Base64-encoded encrypted response
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
+    pub timestamp: i64,
+    pub signature: String, // Base64-encoded signature over server_did|client_did|response|timestamp
 }
 
 /// Agent metadata for registration
@@ -134,6 +187,12 @@ pub struct ServerDidResponse {
     pub did: String,
 }
 
+/// Server signing public key response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerPublicKeyResponse {
+    pub public_key: String, // Base64-encoded Ed25519 public key
+}
+
 /// Agent registration response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegisterResponse {